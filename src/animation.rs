@@ -16,6 +16,7 @@ const LINE_WIDTH_ORIGINAL: f32 = 1.0;
 const LINE_WIDTH_RECONSTRUCTED: f32 = 2.0;
 const ARROW_WIDTH: f32 = 2.0;
 const DEFAULT_SPEED: f64 = 50.0; // points per second at 60 FPS
+const RECONSTRUCTION_OVERSAMPLING: usize = 4; // reconstructed curve points per input sample
 
 #[derive(Clone)]
 struct Viewport {
@@ -43,6 +44,7 @@ pub struct Fourier {
     fourier: math::Fourier, // Single Fourier transform where real=x, imag=y
     k_min: usize,
     k_max: usize,
+    reconstruction: Vec<Complex32>, // Band-limited curve, oversampled by RECONSTRUCTION_OVERSAMPLING
 
     // Canvas
     canvas: canvas::Canvas,
@@ -100,10 +102,12 @@ impl Fourier {
     /// # Returns
     /// A `Result` containing the `Fourier` instance or an error message.
     pub fn from_fourier(fourier: math::Fourier, k_min: usize, k_max: usize) -> Result<Self, String> {
+        let reconstruction = Self::reconstruct(&fourier, k_min, k_max);
         let fourier_struct = Fourier {
             fourier,
             k_min,
             k_max,
+            reconstruction,
             canvas: canvas::Canvas::from_element("animation-canvas"),
             current_point: 0.0,
             point_speed: DEFAULT_SPEED,
@@ -357,19 +361,27 @@ impl Fourier {
         self.canvas.stroke_curve(&x_pixels, &y_pixels, LINE_WIDTH_ORIGINAL, canvas::TAB_BLUE);
     }
 
+    /// Computes the band-limited reconstruction between `k_min` and `k_max`.
+    /// It is resampled so the curve stays smooth between input samples.
+    fn reconstruct(fourier: &math::Fourier, k_min: usize, k_max: usize) -> Vec<Complex32> {
+        fourier.filtered_range(k_min, k_max)
+            .and_then(math::Fourier::from_complex)
+            .and_then(|recon| recon.resample(fourier.size() * RECONSTRUCTION_OVERSAMPLING))
+            .unwrap_or_else(|_| vec![Complex32::new(0.0, 0.0); fourier.size() * RECONSTRUCTION_OVERSAMPLING])
+    }
+
     /// Plots the reconstructed curve up to the current frequency on the canvas.
     fn plot_reconstructed_curve(&self, current_point: usize) {
-        let recon_complex = self.fourier.filtered_range(self.k_min, self.k_max).unwrap_or_else(|_| vec![Complex32::new(0.0, 0.0); self.fourier.size()]);
-
+        let recon_complex = &self.reconstruction;
         if current_point < 2 || recon_complex.len() < 2 { return; }
 
-        let end_point = (current_point + 1).min(recon_complex.len());
+        let end_point = (current_point * RECONSTRUCTION_OVERSAMPLING + 1).min(recon_complex.len());
         let mut x_pixels = Vec::with_capacity(end_point);
         let mut y_pixels = Vec::with_capacity(end_point);
 
-        for i in 0..end_point {
+        for complex_val in &recon_complex[..end_point] {
             // Extract x (real) and y (imaginary) parts
-            let (x_px, y_px) = self.viewport_to_canvas(recon_complex[i].re, recon_complex[i].im);
+            let (x_px, y_px) = self.viewport_to_canvas(complex_val.re, complex_val.im);
             x_pixels.push(x_px);
             y_pixels.push(y_px);
         }
@@ -380,7 +392,7 @@ impl Fourier {
     /// Plots the Fourier components as vectors on the canvas.
     fn plot_fourier_components(&self, current_point: usize) {
         let mut current_complex = Complex32::new(0.0, 0.0);
-        let total_points = self.fourier.size();
+        let total_points = self.fourier.transform_size();
 
         // Draw positive frequencies (including DC)
         for k in self.k_min..=self.k_max {
@@ -0,0 +1,275 @@
+#![allow(dead_code)]
+
+/// Reader for numeric tables in CSV, TSV and similar text formats.
+///
+/// The delimiter (tab, semicolon, comma, pipe or runs of whitespace) is sniffed from the first lines,
+/// and the first line is taken as a header when it is not entirely numeric. Blank lines and lines
+/// starting with '#' are skipped. Fields may be enclosed in double quotes, and with a semicolon
+/// delimiter a decimal comma is accepted. Errors report the (1-based) line of the file.
+use crate::math::{self, Complex};
+
+/// Number of data lines examined to choose the delimiter.
+const SNIFF_LINES: usize = 20;
+
+/// Largest relative deviation of a time step from the mean step for the time column to count as uniform.
+const TIME_STEP_TOLERANCE: f64 = 1e-3;
+
+/// Separator between the fields of a line.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Delimiter {
+    Char(char),
+    /// Any run of spaces and tabs.
+    Whitespace,
+    /// Single-column file: every line is one field.
+    None,
+}
+
+impl Delimiter {
+    /// Splits a line into trimmed fields, honouring double quotes ("" is an escaped quote).
+    fn split(&self, line: &str) -> Vec<String> {
+        let separator = match *self {
+            Delimiter::Whitespace => return line.split_whitespace().map(|field| field.trim_matches('"').to_string()).collect(),
+            Delimiter::None => return vec![line.trim().trim_matches('"').to_string()],
+            Delimiter::Char(separator) => separator,
+        };
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut in_quotes = false;
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if in_quotes && chars.peek() == Some(&'"') => { field.push('"'); chars.next(); },
+                '"' => in_quotes = !in_quotes,
+                c if c == separator && !in_quotes => fields.push(std::mem::take(&mut field).trim().to_string()),
+                c => field.push(c),
+            }
+        }
+        fields.push(field.trim().to_string());
+        fields
+    }
+}
+
+/// A table of numbers read by `parse`, stored column by column.
+pub struct Table {
+    delimiter: Delimiter,
+    headers: Option<Vec<String>>,
+    columns: Vec<Vec<f64>>,
+    // File line of every row, for error messages.
+    lines: Vec<usize>,
+}
+
+/// Columns of a `Table` forming a signal, as 0-based indices.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColumnSelection {
+    /// Sample times in seconds, which must be uniformly spaced; they set the sample rate.
+    pub time: Option<usize>,
+    /// Sample values (real part for complex signals).
+    pub value: usize,
+    /// Imaginary part of the samples, making the signal complex.
+    pub imaginary: Option<usize>,
+}
+
+/// A signal extracted from a `Table`.
+pub struct TableSignal {
+    /// Samples; the imaginary parts are zero for real signals.
+    pub samples: Vec<Complex<f32>>,
+    /// Whether an imaginary column was selected.
+    pub complex: bool,
+    /// Sample rate in Hz derived from the time column, if one was selected.
+    pub sample_rate: Option<f32>,
+}
+
+impl TableSignal {
+    /// Transforms the signal (with `from_real` or `from_complex`), attaching the sample rate of the time column
+    /// or else `default_sample_rate`.
+    /// Returns an error if the samples or the sample rate are invalid.
+    pub fn into_fourier(self, default_sample_rate: f32) -> Result<math::Fourier, String> {
+        let sample_rate = self.sample_rate.unwrap_or(default_sample_rate);
+        let fourier = if self.complex {
+            math::Fourier::from_complex(self.samples)?
+        } else {
+            math::Fourier::from_real(self.samples.iter().map(|c| c.re).collect())?
+        };
+        fourier.with_sample_rate(sample_rate)
+    }
+}
+
+/// Parses a delimited table of numbers.
+/// Returns an error, with its line number, if a line has the wrong number of fields or a field is not a number,
+/// or if the text contains no data.
+pub fn parse(text: &str) -> Result<Table, String> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let content: Vec<(usize, &str)> = text.lines().enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+    if content.is_empty() { return Err("The file contains no data".to_string()); }
+
+    let delimiter = sniff_delimiter(content.iter().map(|(_, line)| *line));
+    let decimal_comma = delimiter == Delimiter::Char(';');
+    let parse_field = |field: &str| -> Option<f64> {
+        let value = if decimal_comma { field.replace(',', ".").parse() } else { field.parse() };
+        value.ok().filter(|value: &f64| value.is_finite())
+    };
+
+    // The first line is a header unless every field is a number
+    let first = delimiter.split(content[0].1);
+    let has_header = first.iter().any(|field| parse_field(field).is_none());
+    let headers = if has_header { Some(first.clone()) } else { None };
+    let width = first.len();
+
+    let mut columns = vec![Vec::new(); width];
+    let mut lines = Vec::new();
+    for &(line_number, line) in content.iter().skip(has_header as usize) {
+        let fields = delimiter.split(line);
+        if fields.len() != width {
+            return Err(format!("Line {}: expected {} fields, found {}", line_number, width, fields.len()));
+        }
+        for (column, field) in fields.iter().enumerate() {
+            let value = parse_field(field)
+                .ok_or_else(|| format!("Line {}, column {}: \"{}\" is not a number", line_number, column + 1, field))?;
+            columns[column].push(value);
+        }
+        lines.push(line_number);
+    }
+    if lines.is_empty() { return Err("The file contains a header but no data".to_string()); }
+
+    Ok(Table { delimiter, headers, columns, lines })
+}
+
+impl Table {
+    /// Extracts the selected columns as a signal.
+    /// Returns an error if a column does not exist, or the time column is not increasing and uniform.
+    pub fn signal(&self, selection: ColumnSelection) -> Result<TableSignal, String> {
+        let column = |index: usize| self.column(index)
+            .ok_or_else(|| format!("Column {} does not exist (the table has {})", index + 1, self.num_columns()));
+        let real = column(selection.value)?;
+        let imaginary = selection.imaginary.map(column).transpose()?;
+        let sample_rate = selection.time.map(|index| self.sample_rate(column(index)?)).transpose()?;
+
+        let samples = (0..self.num_rows())
+            .map(|i| Complex::new(real[i] as f32, imaginary.map_or(0.0, |imaginary| imaginary[i] as f32)))
+            .collect();
+        Ok(TableSignal { samples, complex: imaginary.is_some(), sample_rate })
+    }
+
+    /// Returns the values of a column (0-based), if it exists.
+    pub fn column(&self, index: usize) -> Option<&[f64]> { self.columns.get(index).map(|column| column.as_slice()) }
+    /// Returns the index of the column with the given header, if any.
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.headers.as_ref()?.iter().position(|header| header == name)
+    }
+    /// Returns the header fields, if the table has a header line.
+    pub fn headers(&self) -> Option<&[String]> { self.headers.as_deref() }
+    /// Returns the delimiter found in the file.
+    pub fn delimiter(&self) -> Delimiter { self.delimiter }
+    /// Returns the number of columns.
+    pub fn num_columns(&self) -> usize { self.columns.len() }
+    /// Returns the number of data rows.
+    pub fn num_rows(&self) -> usize { self.lines.len() }
+
+    /// Sample rate of a time column: the inverse of its mean step, if all steps match it.
+    fn sample_rate(&self, times: &[f64]) -> Result<f32, String> {
+        if times.len() < 2 { return Err("The time column needs at least two rows".to_string()); }
+        let step = (times[times.len() - 1] - times[0]) / (times.len() - 1) as f64;
+        if step <= 0.0 { return Err("The time column must be increasing".to_string()); }
+        for (i, pair) in times.windows(2).enumerate() {
+            if ((pair[1] - pair[0]) - step).abs() > TIME_STEP_TOLERANCE * step {
+                return Err(format!("Line {}: time step {} differs from the mean step {} (samples must be uniform)",
+                                   self.lines[i + 1], pair[1] - pair[0], step));
+            }
+        }
+        Ok((1.0 / step) as f32)
+    }
+}
+
+/// Chooses the first delimiter giving the same number of fields (more than one) on all sniffed lines.
+/// Semicolons are tried before commas, since files separated by semicolons often use decimal commas.
+/// Falls back to a single column.
+fn sniff_delimiter<'a, I: Iterator<Item = &'a str>>(lines: I) -> Delimiter {
+    let sample: Vec<&str> = lines.take(SNIFF_LINES).collect();
+    let candidates = [Delimiter::Char('\t'), Delimiter::Char(';'), Delimiter::Char(','), Delimiter::Char('|'), Delimiter::Whitespace];
+    candidates.into_iter()
+        .find(|delimiter| {
+            let width = delimiter.split(sample[0]).len();
+            width > 1 && sample.iter().all(|line| delimiter.split(line).len() == width)
+        })
+        .unwrap_or(Delimiter::None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delimiter_is_sniffed() {
+        let table = parse("\u{feff}time,value,imag\n# comment\n0,1,0\n0.1,2,1\n\n0.2,3,2\n").unwrap();
+        assert_eq!(table.delimiter(), Delimiter::Char(','));
+        assert_eq!(table.headers().unwrap(), &["time", "value", "imag"]);
+        assert_eq!(table.num_columns(), 3);
+        assert_eq!(table.num_rows(), 3);
+        assert_eq!(table.column(2).unwrap(), &[0.0, 1.0, 2.0]);
+        assert_eq!(table.column_index("value"), Some(1));
+
+        // Tab wins over the space inside a header
+        assert_eq!(parse("a\tb c\n1\t2\n").unwrap().delimiter(), Delimiter::Char('\t'));
+        assert_eq!(parse("1|2\n3|4\n").unwrap().delimiter(), Delimiter::Char('|'));
+        assert_eq!(parse("1  2\n3 4\n").unwrap().delimiter(), Delimiter::Whitespace);
+        // A comma inside quotes does not count
+        let table = parse("\"a,b\",c\n1,2\n").unwrap();
+        assert_eq!(table.headers().unwrap()[0], "a,b");
+
+        let table = parse("Voltage (V)\n1\n2\n").unwrap();
+        assert_eq!(table.delimiter(), Delimiter::None);
+        assert_eq!(table.num_columns(), 1);
+        assert_eq!(table.column(0).unwrap(), &[1.0, 2.0]);
+    }
+
+    #[test]
+    fn semicolon_allows_decimal_commas() {
+        let table = parse("t;v\n0,0;1,5\n0,5;-2,5e1\n").unwrap();
+        assert_eq!(table.delimiter(), Delimiter::Char(';'));
+        assert_eq!(table.column(0).unwrap(), &[0.0, 0.5]);
+        assert_eq!(table.column(1).unwrap(), &[1.5, -25.0]);
+
+        // With a comma delimiter, "1,5" is two fields
+        assert_eq!(parse("1,5\n2,5\n").unwrap().num_columns(), 2);
+    }
+
+    #[test]
+    fn errors_report_the_file_line() {
+        let error = parse("a,b\n1,2\n3,x\n").err().unwrap();
+        assert_eq!(error, "Line 3, column 2: \"x\" is not a number");
+
+        // The bad line comes after the sniffed ones, and comments and blank lines still count
+        let mut text = "a,b\n# comment\n\n".to_string();
+        text.push_str(&"1,2\n".repeat(SNIFF_LINES));
+        text.push_str("1,2,3\n");
+        let error = parse(&text).err().unwrap();
+        assert!(error.starts_with(&format!("Line {}: expected 2 fields, found 3", SNIFF_LINES + 4)), "{}", error);
+
+        let table = parse("0,1\n1,2\n3,3\n").unwrap();
+        let error = table.signal(ColumnSelection { time: Some(0), value: 1, imaginary: None }).err().unwrap();
+        assert!(error.starts_with("Line 2: time step"), "{}", error);
+        assert!(table.signal(ColumnSelection { time: None, value: 5, imaginary: None }).is_err());
+
+        assert!(parse("").is_err());
+        assert!(parse("# only a comment\n\n").is_err());
+        assert!(parse("a,b\n").is_err());
+    }
+
+    #[test]
+    fn time_column_sets_the_sample_rate() {
+        let table = parse("time,value,imag\n0,1,0\n0.1,2,1\n0.2,3,2\n").unwrap();
+        let signal = table.signal(ColumnSelection { time: Some(0), value: 1, imaginary: Some(2) }).unwrap();
+        assert!(signal.complex);
+        assert!((signal.sample_rate.unwrap() - 10.0).abs() < 1e-3);
+        assert_eq!(signal.samples[2], Complex::new(3.0, 2.0));
+        let fourier = signal.into_fourier(1.0).unwrap();
+        assert_eq!(fourier.size(), 3);
+
+        let signal = table.signal(ColumnSelection { time: None, value: 1, imaginary: None }).unwrap();
+        assert!(!signal.complex);
+        assert_eq!(signal.sample_rate, None);
+    }
+}
@@ -0,0 +1,498 @@
+#![allow(dead_code)]
+
+/// Small expression language for user-defined signals of the time `t`.
+///
+/// An expression may be preceded by constant definitions separated by semicolons, e.g.
+/// `f = 3; a = 0.5; a * sin(2*pi*f*t) + (1 - a) * sign(sin(2*pi*f*t))`. It supports:
+/// - numbers (`2`, `0.5`, `.5`, `1e-3`), the variable `t` and the constants `pi`, `tau` and `e`
+/// - `+ - * / %` (remainder with the sign of the divisor), `^` (power, right-associative and binding
+///   tighter than unary minus, so `-t^2` is `-(t^2)`) and parentheses
+/// - comparisons `< <= > >= == !=` and the logical operators `&& || !`, which give 1 (true) or 0 (false)
+/// - the functions `sin cos tan exp ln sqrt abs floor ceil round sign` of one argument and `min max`
+///   of two, and the piecewise `if(condition, value if nonzero, value if zero)`
+///
+/// Errors report the (1-based) character position in the text.
+use std::f64::consts::{E, PI, TAU};
+
+/// Deepest nesting of parentheses, calls and prefix operators, which bounds the recursion of the parser.
+const MAX_DEPTH: usize = 256;
+
+/// Names that cannot be redefined as constants.
+const RESERVED: [&str; 5] = ["t", "pi", "tau", "e", "if"];
+
+/// Function applied to evaluated arguments.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Function {
+    Sin, Cos, Tan, Exp, Ln, Sqrt, Abs, Floor, Ceil, Round, Sign, Min, Max,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Function> {
+        Some(match name {
+            "sin" => Function::Sin, "cos" => Function::Cos, "tan" => Function::Tan,
+            "exp" => Function::Exp, "ln" => Function::Ln, "sqrt" => Function::Sqrt,
+            "abs" => Function::Abs, "floor" => Function::Floor, "ceil" => Function::Ceil,
+            "round" => Function::Round, "sign" => Function::Sign,
+            "min" => Function::Min, "max" => Function::Max,
+            _ => return None,
+        })
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            Function::Min | Function::Max => 2,
+            _ => 1,
+        }
+    }
+
+    fn apply(&self, args: &[f64]) -> f64 {
+        let x = args[0];
+        match self {
+            Function::Sin => x.sin(),
+            Function::Cos => x.cos(),
+            Function::Tan => x.tan(),
+            Function::Exp => x.exp(),
+            Function::Ln => x.ln(),
+            Function::Sqrt => x.sqrt(),
+            Function::Abs => x.abs(),
+            Function::Floor => x.floor(),
+            Function::Ceil => x.ceil(),
+            Function::Round => x.round(),
+            // Unlike f64::signum, the sign of zero is zero
+            Function::Sign => if x > 0.0 { 1.0 } else if x < 0.0 { -1.0 } else { 0.0 },
+            Function::Min => x.min(args[1]),
+            Function::Max => x.max(args[1]),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BinaryOp {
+    Add, Sub, Mul, Div, Rem, Pow, Less, LessEqual, Greater, GreaterEqual, Equal, NotEqual, And, Or,
+}
+
+impl BinaryOp {
+    fn apply(&self, a: f64, b: f64) -> f64 {
+        let truth = |condition: bool| if condition { 1.0 } else { 0.0 };
+        match self {
+            BinaryOp::Add => a + b,
+            BinaryOp::Sub => a - b,
+            BinaryOp::Mul => a * b,
+            BinaryOp::Div => a / b,
+            BinaryOp::Rem => a - b * (a / b).floor(),
+            BinaryOp::Pow => a.powf(b),
+            BinaryOp::Less => truth(a < b),
+            BinaryOp::LessEqual => truth(a <= b),
+            BinaryOp::Greater => truth(a > b),
+            BinaryOp::GreaterEqual => truth(a >= b),
+            BinaryOp::Equal => truth(a == b),
+            BinaryOp::NotEqual => truth(a != b),
+            BinaryOp::And => truth(a != 0.0 && b != 0.0),
+            BinaryOp::Or => truth(a != 0.0 || b != 0.0),
+        }
+    }
+}
+
+/// Node of the syntax tree.
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Number(f64),
+    Time,
+    Negate(Box<Node>),
+    Not(Box<Node>),
+    Binary(BinaryOp, Box<Node>, Box<Node>),
+    Call(Function, Vec<Node>),
+    If(Box<Node>, Box<Node>, Box<Node>),
+}
+
+impl Node {
+    fn evaluate(&self, t: f64) -> f64 {
+        match self {
+            Node::Number(value) => *value,
+            Node::Time => t,
+            Node::Negate(operand) => -operand.evaluate(t),
+            Node::Not(operand) => if operand.evaluate(t) == 0.0 { 1.0 } else { 0.0 },
+            Node::Binary(op, a, b) => op.apply(a.evaluate(t), b.evaluate(t)),
+            Node::Call(function, args) => {
+                let values: Vec<f64> = args.iter().map(|arg| arg.evaluate(t)).collect();
+                function.apply(&values)
+            },
+            // Only the selected branch is evaluated
+            Node::If(condition, then, otherwise) => {
+                if condition.evaluate(t) != 0.0 { then.evaluate(t) } else { otherwise.evaluate(t) }
+            },
+        }
+    }
+
+    fn depends_on_time(&self) -> bool {
+        match self {
+            Node::Number(_) => false,
+            Node::Time => true,
+            Node::Negate(operand) | Node::Not(operand) => operand.depends_on_time(),
+            Node::Binary(_, a, b) => a.depends_on_time() || b.depends_on_time(),
+            Node::Call(_, args) => args.iter().any(|arg| arg.depends_on_time()),
+            Node::If(condition, then, otherwise) => {
+                condition.depends_on_time() || then.depends_on_time() || otherwise.depends_on_time()
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    /// Operator or punctuation, e.g. "+", "<=", "(" or ";"
+    Symbol(&'static str),
+}
+
+/// Symbols, with the two-character ones first so that they are matched before their prefixes.
+const SYMBOLS: [&str; 20] = ["<=", ">=", "==", "!=", "&&", "||", "+", "-", "*", "/", "%", "^", "<", ">", "!", "(", ")", ",", ";", "="];
+
+/// Splits the text into tokens with their (1-based) character positions.
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let position = i + 1;
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit())) {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') { i += 1; }
+            // Exponent, only if digits follow so that "2e" stays an error rather than a silent 2
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                let sign = usize::from(chars.get(i + 1).is_some_and(|&s| s == '+' || s == '-'));
+                if chars.get(i + 1 + sign).is_some_and(|d| d.is_ascii_digit()) {
+                    i += 1 + sign;
+                    while i < chars.len() && chars[i].is_ascii_digit() { i += 1; }
+                }
+            }
+            let literal: String = chars[start..i].iter().collect();
+            let value = literal.parse().map_err(|_| format!("Position {}: invalid number \"{}\"", position, literal))?;
+            tokens.push((Token::Number(value), position));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') { i += 1; }
+            tokens.push((Token::Name(chars[start..i].iter().collect()), position));
+        } else {
+            let symbol = SYMBOLS.iter()
+                .find(|symbol| symbol.chars().enumerate().all(|(j, s)| chars.get(i + j) == Some(&s)))
+                .ok_or_else(|| format!("Position {}: unexpected character '{}'", position, c))?;
+            tokens.push((Token::Symbol(symbol), position));
+            i += symbol.len();
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser over the tokens, resolving constant names as it goes.
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    index: usize,
+    // Position reported at the end of the text
+    end: usize,
+    constants: Vec<(String, f64)>,
+    // Nesting of `unary`, through which every nested subexpression is parsed
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> { self.tokens.get(self.index).map(|(token, _)| token) }
+
+    fn position(&self) -> usize { self.tokens.get(self.index).map_or(self.end, |&(_, position)| position) }
+
+    /// Consumes the next token if it is the given symbol.
+    fn accept(&mut self, symbol: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol);
+        if found { self.index += 1; }
+        found
+    }
+
+    /// Returns the name of the constant defined at the current token, if it starts a definition:
+    /// a name followed by '=' (not '==', which is its own token).
+    fn definition(&self) -> Option<String> {
+        match (self.tokens.get(self.index), self.tokens.get(self.index + 1)) {
+            (Some((Token::Name(name), _)), Some((Token::Symbol("="), _))) => Some(name.clone()),
+            _ => None,
+        }
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), String> {
+        if self.accept(symbol) { Ok(()) } else { Err(self.error(&format!("expected '{}'", symbol))) }
+    }
+
+    fn error(&self, message: &str) -> String {
+        let found = match self.peek() {
+            Some(Token::Number(value)) => format!("found {}", value),
+            Some(Token::Name(name)) => format!("found \"{}\"", name),
+            Some(Token::Symbol(symbol)) => format!("found '{}'", symbol),
+            None => "found the end of the expression".to_string(),
+        };
+        format!("Position {}: {}, {}", self.position(), message, found)
+    }
+
+    /// Parses binary operators of one precedence level, left-associative.
+    fn binary(&mut self, ops: &[(&str, BinaryOp)], next: fn(&mut Self) -> Result<Node, String>) -> Result<Node, String> {
+        let mut node = next(self)?;
+        'outer: loop {
+            for &(symbol, op) in ops {
+                if self.accept(symbol) {
+                    node = Node::Binary(op, Box::new(node), Box::new(next(self)?));
+                    continue 'outer;
+                }
+            }
+            return Ok(node);
+        }
+    }
+
+    fn or(&mut self) -> Result<Node, String> { self.binary(&[("||", BinaryOp::Or)], Self::and) }
+
+    fn and(&mut self) -> Result<Node, String> { self.binary(&[("&&", BinaryOp::And)], Self::comparison) }
+
+    fn comparison(&mut self) -> Result<Node, String> {
+        self.binary(&[("<=", BinaryOp::LessEqual), (">=", BinaryOp::GreaterEqual), ("<", BinaryOp::Less),
+                      (">", BinaryOp::Greater), ("==", BinaryOp::Equal), ("!=", BinaryOp::NotEqual)], Self::sum)
+    }
+
+    fn sum(&mut self) -> Result<Node, String> { self.binary(&[("+", BinaryOp::Add), ("-", BinaryOp::Sub)], Self::product) }
+
+    fn product(&mut self) -> Result<Node, String> {
+        self.binary(&[("*", BinaryOp::Mul), ("/", BinaryOp::Div), ("%", BinaryOp::Rem)], Self::unary)
+    }
+
+    fn unary(&mut self) -> Result<Node, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(format!("Position {}: expression is nested too deeply (more than {} levels)", self.position(), MAX_DEPTH));
+        }
+        self.depth += 1;
+        let node = if self.accept("-") {
+            self.unary().map(|node| Node::Negate(Box::new(node)))
+        } else if self.accept("+") {
+            self.unary()
+        } else if self.accept("!") {
+            self.unary().map(|node| Node::Not(Box::new(node)))
+        } else {
+            self.power()
+        };
+        self.depth -= 1;
+        node
+    }
+
+    fn power(&mut self) -> Result<Node, String> {
+        let base = self.primary()?;
+        if self.accept("^") {
+            // Right-associative, and the exponent may carry its own sign: 2^-t
+            return Ok(Node::Binary(BinaryOp::Pow, Box::new(base), Box::new(self.unary()?)));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<Node, String> {
+        let position = self.position();
+        match self.peek().cloned() {
+            Some(Token::Number(value)) => { self.index += 1; Ok(Node::Number(value)) },
+            Some(Token::Symbol("(")) => {
+                self.index += 1;
+                let node = self.or()?;
+                self.expect(")")?;
+                Ok(node)
+            },
+            Some(Token::Name(name)) => {
+                self.index += 1;
+                if self.accept("(") { return self.call(&name, position); }
+                match name.as_str() {
+                    "t" => Ok(Node::Time),
+                    "pi" => Ok(Node::Number(PI)),
+                    "tau" => Ok(Node::Number(TAU)),
+                    "e" => Ok(Node::Number(E)),
+                    _ => self.constants.iter().rev()
+                        .find(|(constant, _)| *constant == name)
+                        .map(|(_, value)| Node::Number(*value))
+                        .ok_or_else(|| format!("Position {}: unknown name \"{}\"", position, name)),
+                }
+            },
+            _ => Err(self.error("expected a number, name or '('")),
+        }
+    }
+
+    /// Parses the arguments of a function call whose '(' was consumed.
+    fn call(&mut self, name: &str, position: usize) -> Result<Node, String> {
+        let mut args = vec![self.or()?];
+        while self.accept(",") { args.push(self.or()?); }
+        self.expect(")")?;
+
+        if name == "if" {
+            if args.len() != 3 {
+                return Err(format!("Position {}: if takes 3 arguments (condition, then, else), got {}", position, args.len()));
+            }
+            let otherwise = args.pop().unwrap();
+            let then = args.pop().unwrap();
+            let condition = args.pop().unwrap();
+            return Ok(Node::If(Box::new(condition), Box::new(then), Box::new(otherwise)));
+        }
+        let function = Function::from_name(name)
+            .ok_or_else(|| format!("Position {}: unknown function \"{}\"", position, name))?;
+        if args.len() != function.arity() {
+            return Err(format!("Position {}: {} takes {} argument(s), got {}", position, name, function.arity(), args.len()));
+        }
+        Ok(Node::Call(function, args))
+    }
+}
+
+/// A parsed expression of the time `t`.
+#[derive(Clone, Debug)]
+pub struct Expression {
+    root: Node,
+}
+
+impl Expression {
+    /// Parses constant definitions followed by an expression (see the module documentation).
+    /// Returns an error, with its position, if the text is not a valid expression, is nested too deeply,
+    /// uses an unknown name or function, or defines a constant that depends on `t`, has a reserved name or is not a finite number.
+    pub fn parse(text: &str) -> Result<Expression, String> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser { tokens, index: 0, end: text.chars().count() + 1, constants: Vec::new(), depth: 0 };
+        while let Some(name) = parser.definition() {
+            let position = parser.position();
+            if RESERVED.contains(&name.as_str()) || Function::from_name(&name).is_some() {
+                return Err(format!("Position {}: \"{}\" is reserved and cannot be defined", position, name));
+            }
+            parser.index += 2;
+            let value = parser.or()?;
+            if value.depends_on_time() {
+                return Err(format!("Position {}: constant \"{}\" cannot depend on t", position, name));
+            }
+            let value = value.evaluate(0.0);
+            if !value.is_finite() {
+                return Err(format!("Position {}: constant \"{}\" is not a finite number ({})", position, name, value));
+            }
+            parser.constants.push((name, value));
+            parser.expect(";")?;
+        }
+
+        let root = parser.or()?;
+        // Allow a trailing semicolon
+        parser.accept(";");
+        if parser.index < parser.tokens.len() { return Err(parser.error("expected an operator or the end of the expression")); }
+        Ok(Expression { root })
+    }
+
+    /// Evaluates the expression at time `t`.
+    pub fn evaluate(&self, t: f64) -> f64 {
+        self.root.evaluate(t)
+    }
+
+    /// Evaluates the expression at `num_samples` times evenly spaced from `t_start` (included) to `t_end` (excluded),
+    /// as for one period of a periodic signal. Returns the times and values.
+    /// Returns an error if the time range or the number of samples is invalid, or a value is not finite.
+    pub fn sample(&self, t_start: f64, t_end: f64, num_samples: usize) -> Result<(Vec<f64>, Vec<f64>), String> {
+        if !t_start.is_finite() || !t_end.is_finite() || t_end <= t_start {
+            return Err(format!("Invalid time range [{}, {}]", t_start, t_end));
+        }
+        if num_samples < 2 { return Err(format!("At least 2 samples are required, got {}", num_samples)); }
+        let step = (t_end - t_start) / num_samples as f64;
+        let times: Vec<f64> = (0..num_samples).map(|i| t_start + i as f64 * step).collect();
+        let values = times.iter()
+            .map(|&t| {
+                let value = self.evaluate(t);
+                if value.is_finite() { Ok(value) } else { Err(format!("The expression is {} at t = {}", value, t)) }
+            })
+            .collect::<Result<Vec<f64>, String>>()?;
+        Ok((times, values))
+    }
+
+    /// Returns whether the expression depends on `t` (otherwise it is a constant signal).
+    pub fn depends_on_time(&self) -> bool {
+        self.root.depends_on_time()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(text: &str, t: f64) -> f64 {
+        Expression::parse(text).unwrap().evaluate(t)
+    }
+
+    fn parse_error(text: &str) -> String {
+        Expression::parse(text).err().unwrap()
+    }
+
+    #[test]
+    fn operators_follow_precedence() {
+        assert_eq!(evaluate("1 + 2 * 3 - 4 / 2", 0.0), 5.0);
+        assert_eq!(evaluate("(1 + 2) * 3", 0.0), 9.0);
+        assert_eq!(evaluate("10 - 4 - 3", 0.0), 3.0);
+        assert_eq!(evaluate("-7 % 3", 0.0), 2.0);
+        // Power is right-associative and binds tighter than unary minus, but takes a signed exponent
+        assert_eq!(evaluate("-2^2", 0.0), -4.0);
+        assert_eq!(evaluate("2^3^2", 0.0), 512.0);
+        assert_eq!(evaluate("2^-1", 0.0), 0.5);
+        assert_eq!(evaluate("2 * t^2", 3.0), 18.0);
+        // Comparisons bind looser than arithmetic, && tighter than ||
+        assert_eq!(evaluate("1 + 1 == 2", 0.0), 1.0);
+        assert_eq!(evaluate("t >= 1 == 1", 1.0), 1.0);
+        assert_eq!(evaluate("t > 1 && t <= 2 || !t", 0.0), 1.0);
+        assert_eq!(evaluate("1 || 0 && 0", 0.0), 1.0);
+    }
+
+    #[test]
+    fn functions_constants_and_definitions() {
+        let expected = (0.2 * PI).sin() + 0.3 * (1.4 * PI).sin();
+        assert!((evaluate("sin(2*pi*t) + 0.3*sin(14*pi*t)", 0.1) - expected).abs() < 1e-12);
+        assert_eq!(evaluate("sign(0) + sign(-3) + abs(-2) + floor(1.7) + sqrt(4) + exp(0) + max(1, 2) + min(1, 2)", 0.0), 8.0);
+        assert_eq!(evaluate("if(t < 0.5, 1, -1)", 0.2), 1.0);
+        assert_eq!(evaluate("if(t < 0.5, 1, -1)", 0.7), -1.0);
+        assert_eq!(evaluate(".5e1 + 1E-1", 0.0), 5.1);
+        assert_eq!(evaluate("tau - 2 * pi + ln(e)", 0.0), 1.0);
+        assert_eq!(evaluate("f = 3; a = f * 2; a + t;", 1.0), 7.0);
+        // A later definition shadows an earlier one
+        assert_eq!(evaluate("a = 1; a = a + 1; a", 0.0), 2.0);
+        assert!(!Expression::parse("a = 2; a * pi").unwrap().depends_on_time());
+        assert!(Expression::parse("sin(t)").unwrap().depends_on_time());
+
+        let (times, values) = Expression::parse("t").unwrap().sample(1.0, 2.0, 4).unwrap();
+        assert_eq!(times, vec![1.0, 1.25, 1.5, 1.75]);
+        assert_eq!(values, times);
+    }
+
+    #[test]
+    fn errors_report_the_position() {
+        assert_eq!(parse_error("sin(t"), "Position 6: expected ')', found the end of the expression");
+        assert_eq!(parse_error("foo(t)"), "Position 1: unknown function \"foo\"");
+        assert_eq!(parse_error("2 * x"), "Position 5: unknown name \"x\"");
+        assert_eq!(parse_error("1 $ 2"), "Position 3: unexpected character '$'");
+        assert_eq!(parse_error("2e"), "Position 2: expected an operator or the end of the expression, found \"e\"");
+        assert!(parse_error("1 2").starts_with("Position 3: expected an operator"));
+        assert!(parse_error("1 +").starts_with("Position 4: expected a number"));
+        assert!(parse_error("").starts_with("Position 1:"));
+        assert!(parse_error("pi = 3; t").contains("reserved"));
+        assert!(parse_error("sin = 3; t").contains("reserved"));
+        assert!(parse_error("a = t; a").contains("cannot depend on t"));
+        assert!(parse_error("a = 1/0; a").contains("not a finite"));
+        assert!(parse_error("a = 1 a").starts_with("Position 7: expected ';'"));
+        assert!(parse_error("min(1)").contains("2 argument"));
+        assert!(parse_error("if(1, 2)").contains("3 arguments"));
+
+        let expression = Expression::parse("1/t").unwrap();
+        assert!(expression.sample(0.0, 1.0, 4).err().unwrap().contains("t = 0"));
+        assert!(expression.sample(1.0, 1.0, 4).is_err());
+        assert!(expression.sample(1.0, 2.0, 1).is_err());
+    }
+
+    #[test]
+    fn deep_nesting_is_rejected() {
+        let nested = |depth: usize, open: &str, close: &str| format!("{}t{}", open.repeat(depth), close.repeat(depth));
+        for (open, close) in [("(", ")"), ("-", ""), ("!", ""), ("sin(", ")"), ("2^", "")] {
+            assert!(Expression::parse(&nested(MAX_DEPTH / 2, open, close)).is_ok(), "{}", open);
+            let error = Expression::parse(&nested(100 * MAX_DEPTH, open, close)).err().unwrap();
+            assert!(error.contains("nested too deeply"), "{}", error);
+        }
+        // Long flat expressions are not nested
+        assert!(Expression::parse(&vec!["t"; 10 * MAX_DEPTH].join(" + ")).is_ok());
+    }
+}
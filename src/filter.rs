@@ -0,0 +1,703 @@
+#![allow(dead_code)]
+use std::f64::consts::PI;
+
+use num_complex::Complex64;
+use num_traits::Zero;
+
+use crate::math::{cast, to_f64, Complex, Real, Window};
+
+/// Highest prototype order accepted by the IIR designs; beyond it the root finding and the
+/// pole-zero pairing lose too much precision.
+const MAX_IIR_ORDER: usize = 16;
+
+/// Half length of the `resample_poly` anti-aliasing filter, in periods of the larger factor.
+const RESAMPLE_HALF_LENGTH: usize = 10;
+/// Kaiser shape parameter of the `resample_poly` anti-aliasing filter.
+const RESAMPLE_KAISER_BETA: f32 = 5.0;
+
+/// Frequency response shape of a filter, with cutoffs in Hz.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Response {
+    LowPass(f32),
+    HighPass(f32),
+    /// Passes the frequencies between the two cutoffs.
+    BandPass(f32, f32),
+    /// Rejects the frequencies between the two cutoffs.
+    BandStop(f32, f32),
+}
+
+impl Response {
+    /// Returns an error unless all cutoffs lie strictly between 0 and the Nyquist frequency
+    /// and band edges are increasing.
+    pub fn validate(&self, sample_rate: f64) -> Result<(), String> {
+        if !sample_rate.is_finite() || sample_rate <= 0.0 {
+            return Err(format!("Sample rate must be a positive number, got {}", sample_rate));
+        }
+        let nyquist = sample_rate / 2.0;
+        let check = |cutoff: f32| {
+            let cutoff = cutoff as f64;
+            if cutoff > 0.0 && cutoff < nyquist { Ok(()) }
+            else { Err(format!("Cutoff must be within (0, {}) Hz, got {}", nyquist, cutoff)) }
+        };
+        match *self {
+            Response::LowPass(cutoff) | Response::HighPass(cutoff) => check(cutoff),
+            Response::BandPass(low, high) | Response::BandStop(low, high) => {
+                check(low)?;
+                check(high)?;
+                if low >= high { return Err(format!("Band edges must be increasing, got [{}, {}] Hz", low, high)); }
+                Ok(())
+            },
+        }
+    }
+}
+
+/// Analog prototype used by `Iir::design`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IirDesign {
+    /// Maximally flat pass band.
+    Butterworth,
+    /// Equiripple pass band with the given ripple in dB.
+    ChebyshevI(f32),
+    /// Equiripple stop band with the given minimum attenuation in dB.
+    ChebyshevII(f32),
+    /// Maximally flat group delay, normalized so the cutoff is the -3 dB point.
+    Bessel,
+}
+
+impl IirDesign {
+    /// Returns an error if the ripple or attenuation is not positive.
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            IirDesign::ChebyshevI(ripple) if !(ripple > 0.0 && ripple.is_finite()) =>
+                Err(format!("Chebyshev I pass-band ripple must be positive, got {} dB", ripple)),
+            IirDesign::ChebyshevII(attenuation) if !(attenuation > 0.0 && attenuation.is_finite()) =>
+                Err(format!("Chebyshev II stop-band attenuation must be positive, got {} dB", attenuation)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A linear time-invariant filter applied sample by sample.
+pub trait Filter<T: Real> {
+    /// Filters the data causally, as if the input had been constant at `initial` forever before
+    /// the first sample (the filter starts in its steady state for that input).
+    fn filter_steady(&self, data: &[T], initial: T) -> Vec<T>;
+
+    /// Returns the complex response of the filter at the given frequency in Hz.
+    fn frequency_response(&self, frequency: T) -> Complex<T>;
+
+    /// Number of samples the filter needs to settle, used to extend the edges in `filtfilt`.
+    fn settling_length(&self) -> usize;
+
+    /// Filters the data causally, starting from rest.
+    fn filter(&self, data: &[T]) -> Vec<T> {
+        self.filter_steady(data, T::zero())
+    }
+}
+
+/// Filters the data forwards and then backwards, which cancels the phase of the filter (no delay)
+/// and squares its magnitude response.
+///
+/// Both ends are extended by an odd reflection of the signal and each pass starts in the steady state
+/// of its first sample, which keeps start-up transients out of the result.
+pub fn filtfilt<T: Real, F: Filter<T> + ?Sized>(filter: &F, data: &[T]) -> Vec<T> {
+    if data.is_empty() { return Vec::new(); }
+    let n = data.len();
+    let pad = filter.settling_length().min(n - 1);
+    let two: T = cast(2.0);
+
+    let mut extended = Vec::with_capacity(n + 2 * pad);
+    extended.extend((1..=pad).rev().map(|i| two * data[0] - data[i]));
+    extended.extend_from_slice(data);
+    extended.extend((1..=pad).map(|i| two * data[n - 1] - data[n - 1 - i]));
+
+    let mut forward = filter.filter_steady(&extended, extended[0]);
+    forward.reverse();
+    let mut backward = filter.filter_steady(&forward, forward[0]);
+    backward.reverse();
+    backward[pad..pad + n].to_vec()
+}
+
+/// Resamples the data by the rational factor up / down: upsamples by `up`, low-pass filters at the
+/// lower of the two Nyquist frequencies and keeps every `down`-th sample.
+///
+/// Only the taps meeting non-zero upsampled samples are evaluated (polyphase form), so the cost grows
+/// with the output length rather than with `up`, which suits long signals better than `Fourier::resample`.
+/// The anti-aliasing filter is a Kaiser-windowed sinc whose delay is compensated, and the signal is
+/// taken as zero outside its ends. The output has ceil(N up / down) samples.
+/// Returns an error if a factor is zero or the data is empty or invalid.
+pub fn resample_poly<T: Real>(data: &[T], up: usize, down: usize) -> Result<Vec<T>, String> {
+    if up == 0 || down == 0 { return Err(format!("Resampling factors must be positive, got {}/{}", up, down)); }
+    if data.is_empty() { return Err("Input vector is empty".to_string()); }
+    if data.iter().any(|val| !val.is_finite()) {
+        return Err("Input vector contains invalid values (NaN or Inf)".to_string());
+    }
+    let divisor = gcd(up, down);
+    let (up, down) = (up / divisor, down / divisor);
+    if up == down { return Ok(data.to_vec()); }
+
+    // Sample rates are in units of the input rate, so the upsampled rate is `up`
+    let half_len = RESAMPLE_HALF_LENGTH * up.max(down);
+    let cutoff = 0.5 * (up as f64 / down as f64).min(1.0);
+    let design = Fir::<f64>::windowed_sinc(2 * half_len + 1, Response::LowPass(cutoff as f32), Window::Kaiser(RESAMPLE_KAISER_BETA), up as f64)?;
+    // Upsampling spreads each sample's energy over `up` outputs, so restore the amplitude
+    let taps: Vec<f64> = design.taps().iter().map(|h| h * up as f64).collect();
+
+    let n = data.len();
+    let output_len = (n * up).div_ceil(down);
+    let result = (0..output_len)
+        .map(|i| {
+            // Position in the upsampled, filtered signal, shifted by the filter delay
+            let j = i * down + half_len;
+            let first = (j + 1).saturating_sub(taps.len()).div_ceil(up);
+            let last = (j / up).min(n - 1);
+            let sum: f64 = (first..=last).map(|k| taps[j - k * up] * to_f64(data[k])).sum();
+            cast(sum)
+        })
+        .collect();
+    Ok(result)
+}
+
+/// Finite impulse response filter.
+pub struct Fir<T: Real = f32> {
+    taps: Vec<T>,
+    sample_rate: T,
+}
+
+impl<T: Real> Fir<T> {
+    /// Constructs a filter from its impulse response.
+    /// Returns an error if the taps are empty or invalid, or the sample rate is not positive.
+    pub fn from_taps(taps: Vec<T>, sample_rate: T) -> Result<Self, String> {
+        if taps.is_empty() { return Err("Filter taps are empty".to_string()); }
+        if taps.iter().any(|val| !val.is_finite()) {
+            return Err("Filter taps contain invalid values (NaN or Inf)".to_string());
+        }
+        if !sample_rate.is_finite() || sample_rate <= T::zero() {
+            return Err(format!("Sample rate must be a positive number, got {}", sample_rate));
+        }
+        Ok(Fir { taps, sample_rate })
+    }
+
+    /// Designs a linear-phase filter by windowing the ideal (sinc) impulse response.
+    ///
+    /// The window is used in its symmetric form, and the taps are scaled to unit gain at the center
+    /// of the pass band (DC for low-pass and band-stop, Nyquist for high-pass).
+    /// High-pass and band-stop filters need an odd number of taps, since an even-length symmetric
+    /// filter always has a zero at the Nyquist frequency.
+    /// Returns an error if the response, the window or the number of taps is invalid.
+    pub fn windowed_sinc(num_taps: usize, response: Response, window: Window, sample_rate: T) -> Result<Self, String> {
+        let fs = to_f64(sample_rate);
+        response.validate(fs)?;
+        window.validate()?;
+        if num_taps == 0 { return Err("Number of taps must be positive".to_string()); }
+        let passes_nyquist = matches!(response, Response::HighPass(_) | Response::BandStop(_, _));
+        if passes_nyquist && num_taps.is_multiple_of(2) {
+            return Err(format!("High-pass and band-stop filters need an odd number of taps, got {}", num_taps));
+        }
+
+        // Ideal responses are built from low-pass sincs, with cutoffs in cycles per sample
+        let center = (num_taps - 1) as f64 / 2.0;
+        let lowpass = |cutoff: f32| -> Vec<f64> {
+            let fc = cutoff as f64 / fs;
+            (0..num_taps).map(|i| 2.0 * fc * sinc(2.0 * fc * (i as f64 - center))).collect()
+        };
+        let delta = |mut taps: Vec<f64>| {
+            taps.iter_mut().for_each(|h| *h = -*h);
+            taps[num_taps / 2] += 1.0;
+            taps
+        };
+        let difference = |high: Vec<f64>, low: Vec<f64>| -> Vec<f64> {
+            high.iter().zip(low.iter()).map(|(h, l)| h - l).collect()
+        };
+        let ideal = match response {
+            Response::LowPass(cutoff) => lowpass(cutoff),
+            Response::HighPass(cutoff) => delta(lowpass(cutoff)),
+            Response::BandPass(low, high) => difference(lowpass(high), lowpass(low)),
+            Response::BandStop(low, high) => delta(difference(lowpass(high), lowpass(low))),
+        };
+
+        let coefficients = symmetric_window(window, num_taps);
+        let mut taps: Vec<f64> = ideal.iter().zip(coefficients.iter()).map(|(h, w)| h * w).collect();
+
+        // Normalize the gain at the reference frequency (in cycles per sample)
+        let reference = match response {
+            Response::LowPass(_) | Response::BandStop(_, _) => 0.0,
+            Response::HighPass(_) => 0.5,
+            Response::BandPass(low, high) => (low as f64 + high as f64) / (2.0 * fs),
+        };
+        let gain = taps.iter().enumerate()
+            .map(|(i, &h)| h * Complex64::from_polar(1.0, -2.0 * PI * reference * i as f64))
+            .sum::<Complex64>()
+            .norm();
+        taps.iter_mut().for_each(|h| *h /= gain);
+
+        Ok(Fir { taps: taps.into_iter().map(cast).collect(), sample_rate })
+    }
+
+    /// Returns the impulse response of the filter.
+    pub fn taps(&self) -> &[T] { &self.taps }
+    /// Returns the sample rate the filter was designed for, in Hz.
+    pub fn sample_rate(&self) -> T { self.sample_rate }
+}
+
+impl<T: Real> Filter<T> for Fir<T> {
+    fn filter_steady(&self, data: &[T], initial: T) -> Vec<T> {
+        (0..data.len())
+            .map(|n| {
+                self.taps.iter().enumerate()
+                    .map(|(k, &h)| h * if k <= n { data[n - k] } else { initial })
+                    .fold(T::zero(), |acc, x| acc + x)
+            })
+            .collect()
+    }
+
+    fn frequency_response(&self, frequency: T) -> Complex<T> {
+        let omega = to_f64(frequency) / to_f64(self.sample_rate) * 2.0 * PI;
+        let response = self.taps.iter().enumerate()
+            .map(|(k, &h)| to_f64(h) * Complex64::from_polar(1.0, -omega * k as f64))
+            .sum::<Complex64>();
+        Complex::new(cast(response.re), cast(response.im))
+    }
+
+    fn settling_length(&self) -> usize { 3 * self.taps.len() }
+}
+
+/// Infinite impulse response filter, stored as a cascade of second-order sections.
+pub struct Iir<T: Real = f32> {
+    sections: Vec<Biquad<T>>,
+    sample_rate: T,
+}
+
+/// Second-order section (b0 + b1 z^-1 + b2 z^-2) / (1 + a1 z^-1 + a2 z^-2).
+#[derive(Clone, Copy, Debug)]
+struct Biquad<T: Real> {
+    b: [T; 3],
+    a: [T; 2],
+}
+
+impl<T: Real> Iir<T> {
+    /// Designs a digital filter from an analog prototype of the given order.
+    ///
+    /// The prototype is transformed to the requested band and mapped to the z-plane with the bilinear
+    /// transform, with cutoffs pre-warped so they land exactly at the requested frequencies.
+    /// Band-pass and band-stop filters have twice the prototype order.
+    /// Returns an error if the order, response or design parameters are invalid.
+    pub fn design(design: IirDesign, order: usize, response: Response, sample_rate: T) -> Result<Self, String> {
+        let fs = to_f64(sample_rate);
+        response.validate(fs)?;
+        design.validate()?;
+        if order == 0 || order > MAX_IIR_ORDER {
+            return Err(format!("Filter order must be within [1, {}], got {}", MAX_IIR_ORDER, order));
+        }
+
+        let prototype = Zpk::prototype(design, order);
+        let warp = |cutoff: f32| 2.0 * fs * (PI * cutoff as f64 / fs).tan();
+        let analog = match response {
+            Response::LowPass(cutoff) => prototype.lowpass(warp(cutoff)),
+            Response::HighPass(cutoff) => prototype.highpass(warp(cutoff)),
+            Response::BandPass(low, high) => {
+                let (low, high) = (warp(low), warp(high));
+                prototype.bandpass((low * high).sqrt(), high - low)
+            },
+            Response::BandStop(low, high) => {
+                let (low, high) = (warp(low), warp(high));
+                prototype.bandstop((low * high).sqrt(), high - low)
+            },
+        };
+
+        let sections = analog.bilinear(fs).into_sections()?
+            .into_iter()
+            .map(|section| Biquad { b: section.b.map(cast), a: section.a.map(cast) })
+            .collect();
+        Ok(Iir { sections, sample_rate })
+    }
+
+    /// Returns the number of second-order sections.
+    pub fn num_sections(&self) -> usize { self.sections.len() }
+    /// Returns the sample rate the filter was designed for, in Hz.
+    pub fn sample_rate(&self) -> T { self.sample_rate }
+}
+
+impl<T: Real> Filter<T> for Iir<T> {
+    fn filter_steady(&self, data: &[T], initial: T) -> Vec<T> {
+        let mut output = data.to_vec();
+        let mut input_level = initial;
+        for section in &self.sections {
+            let [b0, b1, b2] = section.b;
+            let [a1, a2] = section.a;
+
+            // Steady state of the transposed direct form II for a constant input
+            let dc_gain = (b0 + b1 + b2) / (T::one() + a1 + a2);
+            let output_level = dc_gain * input_level;
+            let mut s1 = output_level - b0 * input_level;
+            let mut s2 = b2 * input_level - a2 * output_level;
+
+            for x in output.iter_mut() {
+                let input = *x;
+                let y = b0 * input + s1;
+                s1 = b1 * input - a1 * y + s2;
+                s2 = b2 * input - a2 * y;
+                *x = y;
+            }
+            input_level = output_level;
+        }
+        output
+    }
+
+    fn frequency_response(&self, frequency: T) -> Complex<T> {
+        let omega = to_f64(frequency) / to_f64(self.sample_rate) * 2.0 * PI;
+        let z1 = Complex64::from_polar(1.0, -omega);
+        let z2 = z1 * z1;
+        let response = self.sections.iter().fold(Complex64::new(1.0, 0.0), |acc, section| {
+            let [b0, b1, b2] = section.b.map(to_f64);
+            let [a1, a2] = section.a.map(to_f64);
+            acc * (b0 + z1 * b1 + z2 * b2) / (1.0 + z1 * a1 + z2 * a2)
+        });
+        Complex::new(cast(response.re), cast(response.im))
+    }
+
+    fn settling_length(&self) -> usize { 3 * (2 * self.sections.len() + 1) }
+}
+
+/// Zeros, poles and gain of a transfer function, in the s-plane or the z-plane.
+struct Zpk {
+    zeros: Vec<Complex64>,
+    poles: Vec<Complex64>,
+    gain: f64,
+}
+
+impl Zpk {
+    /// Analog low-pass prototype with its cutoff at 1 rad/s.
+    fn prototype(design: IirDesign, order: usize) -> Zpk {
+        let n = order as f64;
+        // Angles pi * m / (2n) for m = -n+1, -n+3, ..., n-1
+        let angles: Vec<f64> = (0..order).map(|i| PI * (2.0 * i as f64 - n + 1.0) / (2.0 * n)).collect();
+
+        match design {
+            IirDesign::Butterworth => {
+                let poles = angles.iter().map(|&theta| -Complex64::from_polar(1.0, theta)).collect();
+                Zpk { zeros: Vec::new(), poles, gain: 1.0 }
+            },
+            IirDesign::ChebyshevI(ripple) => {
+                let epsilon = (10f64.powf(0.1 * ripple as f64) - 1.0).sqrt();
+                let mu = (1.0 / epsilon).asinh() / n;
+                let poles: Vec<Complex64> = angles.iter().map(|&theta| -Complex64::new(mu, theta).sinh()).collect();
+                let mut gain = product(poles.iter().map(|p| -p)).re;
+                if order.is_multiple_of(2) { gain /= (1.0 + epsilon * epsilon).sqrt(); }
+                Zpk { zeros: Vec::new(), poles, gain }
+            },
+            IirDesign::ChebyshevII(attenuation) => {
+                let epsilon = 1.0 / (10f64.powf(0.1 * attenuation as f64) - 1.0).sqrt();
+                let mu = (1.0 / epsilon).asinh() / n;
+                // The middle angle of odd orders would put a zero at infinity
+                let zeros: Vec<Complex64> = angles.iter()
+                    .filter(|theta| theta.abs() > 1e-12)
+                    .map(|&theta| Complex64::new(0.0, 1.0 / theta.sin()))
+                    .collect();
+                let poles: Vec<Complex64> = angles.iter()
+                    .map(|&theta| {
+                        let p = -Complex64::from_polar(1.0, theta);
+                        1.0 / Complex64::new(mu.sinh() * p.re, mu.cosh() * p.im)
+                    })
+                    .collect();
+                let gain = (product(poles.iter().map(|p| -p)) / product(zeros.iter().map(|z| -z))).re;
+                Zpk { zeros, poles, gain }
+            },
+            IirDesign::Bessel => {
+                let poles = bessel_poles(order);
+                let gain = product(poles.iter().map(|p| -p)).re;
+                Zpk { zeros: Vec::new(), poles, gain }
+            },
+        }
+    }
+
+    /// Number of zeros at infinity (excess of poles over zeros).
+    fn degree(&self) -> usize { self.poles.len() - self.zeros.len() }
+
+    /// Moves the cutoff of a low-pass prototype to `omega` rad/s.
+    fn lowpass(self, omega: f64) -> Zpk {
+        let degree = self.degree();
+        Zpk {
+            zeros: self.zeros.iter().map(|z| z * omega).collect(),
+            poles: self.poles.iter().map(|p| p * omega).collect(),
+            gain: self.gain * omega.powi(degree as i32),
+        }
+    }
+
+    /// Turns a low-pass prototype into a high-pass filter with cutoff `omega` rad/s (s -> omega / s).
+    fn highpass(self, omega: f64) -> Zpk {
+        let degree = self.degree();
+        let gain = self.gain * (product(self.zeros.iter().map(|z| -z)) / product(self.poles.iter().map(|p| -p))).re;
+        let mut zeros: Vec<Complex64> = self.zeros.iter().map(|z| omega / z).collect();
+        zeros.extend(std::iter::repeat_n(Complex64::zero(), degree));
+        Zpk { zeros, poles: self.poles.iter().map(|p| omega / p).collect(), gain }
+    }
+
+    /// Turns a low-pass prototype into a band-pass filter centered at `center` rad/s
+    /// with bandwidth `width` rad/s (s -> (s² + center²) / (s width)).
+    fn bandpass(self, center: f64, width: f64) -> Zpk {
+        let degree = self.degree();
+        let split = |roots: &[Complex64]| -> Vec<Complex64> {
+            let scaled: Vec<Complex64> = roots.iter().map(|r| r * width / 2.0).collect();
+            let offset = |r: &Complex64| (r * r - center * center).sqrt();
+            scaled.iter().map(|r| r + offset(r)).chain(scaled.iter().map(|r| r - offset(r))).collect()
+        };
+        let mut zeros = split(&self.zeros);
+        zeros.extend(std::iter::repeat_n(Complex64::zero(), degree));
+        Zpk { zeros, poles: split(&self.poles), gain: self.gain * width.powi(degree as i32) }
+    }
+
+    /// Turns a low-pass prototype into a band-stop filter centered at `center` rad/s
+    /// with bandwidth `width` rad/s (s -> s width / (s² + center²)).
+    fn bandstop(self, center: f64, width: f64) -> Zpk {
+        let degree = self.degree();
+        let gain = self.gain * (product(self.zeros.iter().map(|z| -z)) / product(self.poles.iter().map(|p| -p))).re;
+        let split = |roots: &[Complex64]| -> Vec<Complex64> {
+            let inverted: Vec<Complex64> = roots.iter().map(|r| width / 2.0 / r).collect();
+            let offset = |r: &Complex64| (r * r - center * center).sqrt();
+            inverted.iter().map(|r| r + offset(r)).chain(inverted.iter().map(|r| r - offset(r))).collect()
+        };
+        let mut zeros = split(&self.zeros);
+        zeros.extend(std::iter::repeat_n(Complex64::new(0.0, center), degree));
+        zeros.extend(std::iter::repeat_n(Complex64::new(0.0, -center), degree));
+        Zpk { zeros, poles: split(&self.poles), gain }
+    }
+
+    /// Maps an analog filter to the z-plane with the bilinear transform s = 2 fs (z - 1) / (z + 1).
+    /// Zeros at infinity end up at the Nyquist frequency (z = -1).
+    fn bilinear(self, sample_rate: f64) -> Zpk {
+        let degree = self.degree();
+        let k = 2.0 * sample_rate;
+        let gain = self.gain * (product(self.zeros.iter().map(|z| k - z)) / product(self.poles.iter().map(|p| k - p))).re;
+        let mut zeros: Vec<Complex64> = self.zeros.iter().map(|z| (k + z) / (k - z)).collect();
+        zeros.extend(std::iter::repeat_n(Complex64::new(-1.0, 0.0), degree));
+        Zpk { zeros, poles: self.poles.iter().map(|p| (k + p) / (k - p)).collect(), gain }
+    }
+
+    /// Groups a digital filter with as many zeros as poles into second-order sections.
+    ///
+    /// Sections are ordered from the poles farthest from the unit circle to the closest ones, and each
+    /// pole pair takes the remaining zeros closest to it, which keeps intermediate gains moderate.
+    /// The overall gain goes into the numerator of the first section.
+    fn into_sections(self) -> Result<Vec<Biquad<f64>>, String> {
+        let mut pole_factors = quadratic_factors(&self.poles)?;
+        let mut zero_factors = quadratic_factors(&self.zeros)?;
+        if pole_factors.len() != zero_factors.len() {
+            return Err("Filter has mismatched numbers of poles and zeros".to_string());
+        }
+        pole_factors.sort_by(|a, b| a.0.norm().partial_cmp(&b.0.norm()).unwrap_or(std::cmp::Ordering::Equal));
+
+        // Pair from the most resonant section down, then restore the ascending order
+        let mut sections = Vec::with_capacity(pole_factors.len());
+        for (root, a) in pole_factors.into_iter().rev() {
+            let nearest = (0..zero_factors.len())
+                .min_by(|&i, &j| {
+                    let di = (zero_factors[i].0 - root).norm();
+                    let dj = (zero_factors[j].0 - root).norm();
+                    di.partial_cmp(&dj).unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .unwrap();
+            let (_, b) = zero_factors.swap_remove(nearest);
+            sections.push(Biquad { b, a: [a[1], a[2]] });
+        }
+        sections.reverse();
+        if let Some(first) = sections.first_mut() {
+            first.b.iter_mut().for_each(|c| *c *= self.gain);
+        }
+        Ok(sections)
+    }
+}
+
+/// Splits the roots of a real polynomial into factors 1 + c1 z^-1 + c2 z^-2 (conjugate pairs or pairs of
+/// real roots, plus one first-order factor for an odd count of real roots), each with a representative root.
+fn quadratic_factors(roots: &[Complex64]) -> Result<Vec<(Complex64, [f64; 3])>, String> {
+    let is_real = |r: &Complex64| r.im.abs() <= 1e-8 * r.norm().max(1.0);
+    let upper: Vec<Complex64> = roots.iter().filter(|r| !is_real(r) && r.im > 0.0).cloned().collect();
+    let lower = roots.iter().filter(|r| !is_real(r) && r.im < 0.0).count();
+    if upper.len() != lower {
+        return Err("Complex roots do not come in conjugate pairs".to_string());
+    }
+
+    let mut factors: Vec<(Complex64, [f64; 3])> = upper.iter()
+        .map(|&r| (r, [1.0, -2.0 * r.re, r.norm_sqr()]))
+        .collect();
+    let mut reals: Vec<f64> = roots.iter().filter(|r| is_real(r)).map(|r| r.re).collect();
+    reals.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    for pair in reals.chunks(2) {
+        match *pair {
+            [r1, r2] => factors.push((Complex64::new(r1.abs().max(r2.abs()), 0.0), [1.0, -(r1 + r2), r1 * r2])),
+            [r] => factors.push((Complex64::new(r, 0.0), [1.0, -r, 0.0])),
+            _ => unreachable!(),
+        }
+    }
+    Ok(factors)
+}
+
+/// Poles of the Bessel prototype of the given order, scaled so that the magnitude response
+/// is 1/sqrt(2) (-3 dB) at 1 rad/s.
+fn bessel_poles(order: usize) -> Vec<Complex64> {
+    // Reverse Bessel polynomial: a_k = (2n - k)! / (2^(n-k) k! (n-k)!), computed from a_n = 1 downwards
+    let mut coefficients = vec![1.0; order + 1];
+    for k in (0..order).rev() {
+        coefficients[k] = coefficients[k + 1] * ((2 * order - k) * (k + 1)) as f64 / (2 * (order - k)) as f64;
+    }
+    let poles = polynomial_roots(&coefficients);
+
+    // |H(i w)| = prod |p| / prod |i w - p| decreases monotonically; bisect for the -3 dB point
+    let magnitude = |omega: f64| {
+        poles.iter().map(|p| p.norm() / (Complex64::new(0.0, omega) - p).norm()).product::<f64>()
+    };
+    let (mut low, mut high) = (0.0, 1.0);
+    while magnitude(high) > std::f64::consts::FRAC_1_SQRT_2 { high *= 2.0; }
+    for _ in 0..100 {
+        let mid = 0.5 * (low + high);
+        if magnitude(mid) > std::f64::consts::FRAC_1_SQRT_2 { low = mid; } else { high = mid; }
+    }
+    let cutoff = 0.5 * (low + high);
+    poles.iter().map(|p| p / cutoff).collect()
+}
+
+/// Roots of a monic polynomial given by its coefficients in increasing powers, using the
+/// Durand-Kerner iteration.
+pub(crate) fn polynomial_roots(coefficients: &[f64]) -> Vec<Complex64> {
+    let degree = coefficients.len() - 1;
+    let evaluate = |x: Complex64| coefficients.iter().rev().fold(Complex64::zero(), |acc, &c| acc * x + c);
+
+    // Start on a circle of the expected root radius, away from the real axis
+    let radius = coefficients[0].abs().powf(1.0 / degree as f64).max(1.0);
+    let mut roots: Vec<Complex64> = (0..degree)
+        .map(|i| Complex64::from_polar(radius, 2.0 * PI * i as f64 / degree as f64 + 0.4))
+        .collect();
+
+    for _ in 0..1000 {
+        let mut largest_step: f64 = 0.0;
+        for i in 0..degree {
+            let denominator = (0..degree).filter(|&j| j != i).fold(Complex64::new(1.0, 0.0), |acc, j| acc * (roots[i] - roots[j]));
+            let step = evaluate(roots[i]) / denominator;
+            roots[i] -= step;
+            largest_step = largest_step.max(step.norm());
+        }
+        if largest_step < 1e-14 * radius { break; }
+    }
+    roots
+}
+
+/// Symmetric form of the window (both ends included), as used for FIR design.
+fn symmetric_window(window: Window, total_points: usize) -> Vec<f64> {
+    if total_points <= 1 { return vec![1.0; total_points]; }
+    // A periodic window of length N-1 plus its first sample repeated is the symmetric window of length N
+    let mut coefficients = window.coefficients::<f64>(total_points - 1);
+    coefficients.push(coefficients[0]);
+    coefficients
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-12 { 1.0 } else { (PI * x).sin() / (PI * x) }
+}
+
+fn product<I: Iterator<Item = Complex64>>(values: I) -> Complex64 {
+    values.fold(Complex64::new(1.0, 0.0), |acc, x| acc * x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f64 = 1000.0;
+
+    fn gain_db<F: Filter<f64>>(filter: &F, frequency: f64) -> f64 {
+        20.0 * filter.frequency_response(frequency).norm().log10()
+    }
+
+    #[test]
+    fn butterworth_is_3db_down_at_cutoff() {
+        for order in [1, 2, 4, 7] {
+            let lowpass = Iir::design(IirDesign::Butterworth, order, Response::LowPass(100.0), SAMPLE_RATE).unwrap();
+            assert!((gain_db(&lowpass, 100.0) + 3.0103).abs() < 1e-3, "order {}: {} dB", order, gain_db(&lowpass, 100.0));
+            assert!(gain_db(&lowpass, 0.0).abs() < 1e-9);
+
+            let highpass = Iir::design(IirDesign::Butterworth, order, Response::HighPass(100.0), SAMPLE_RATE).unwrap();
+            assert!((gain_db(&highpass, 100.0) + 3.0103).abs() < 1e-3, "order {}", order);
+            assert!(gain_db(&highpass, 500.0).abs() < 1e-9);
+        }
+        let bandpass = Iir::design(IirDesign::Butterworth, 3, Response::BandPass(100.0, 200.0), SAMPLE_RATE).unwrap();
+        assert!((gain_db(&bandpass, 100.0) + 3.0103).abs() < 1e-3);
+        assert!((gain_db(&bandpass, 200.0) + 3.0103).abs() < 1e-3);
+        assert!(gain_db(&bandpass, 10.0) < -40.0);
+    }
+
+    #[test]
+    fn chebyshev_i_is_ripple_down_at_cutoff() {
+        for order in [2, 3, 5, 6] {
+            let lowpass = Iir::design(IirDesign::ChebyshevI(1.0), order, Response::LowPass(100.0), SAMPLE_RATE).unwrap();
+            assert!((gain_db(&lowpass, 100.0) + 1.0).abs() < 1e-3, "order {}: {} dB", order, gain_db(&lowpass, 100.0));
+            // The pass band stays within the ripple
+            for f in (0..100).step_by(5) {
+                let gain = gain_db(&lowpass, f as f64);
+                assert!((-1.0 - 1e-6..=1e-6).contains(&gain), "order {}, {} Hz: {} dB", order, f, gain);
+            }
+        }
+    }
+
+    #[test]
+    fn filtering_matches_frequency_response() {
+        // A sinusoid in the steady state comes out scaled by the magnitude response
+        let filter = Iir::design(IirDesign::Butterworth, 4, Response::LowPass(100.0), SAMPLE_RATE).unwrap();
+        let frequency = 120.0;
+        let data: Vec<f64> = (0..4000).map(|i| (2.0 * PI * frequency * i as f64 / SAMPLE_RATE).sin()).collect();
+        let output = filter.filter(&data);
+        let peak = output[2000..].iter().fold(0.0f64, |acc, &x| acc.max(x.abs()));
+        assert!((peak - filter.frequency_response(frequency).norm()).abs() < 1e-3);
+
+        // Zero-phase filtering squares the magnitude and keeps a constant unchanged
+        let zero_phase = filtfilt(&filter, &data);
+        let peak = zero_phase[2000..3000].iter().fold(0.0f64, |acc, &x| acc.max(x.abs()));
+        assert!((peak - filter.frequency_response(frequency).norm_sqr()).abs() < 1e-3);
+        assert!(filtfilt(&filter, &[2.0; 50]).iter().all(|&x| (x - 2.0).abs() < 1e-9));
+    }
+
+    #[test]
+    fn windowed_sinc_is_6db_down_at_cutoff() {
+        let fir = Fir::windowed_sinc(101, Response::LowPass(100.0), Window::Hamming, SAMPLE_RATE).unwrap();
+        assert!((fir.frequency_response(100.0).norm() - 0.5).abs() < 1e-2);
+        assert!((fir.frequency_response(0.0).norm() - 1.0).abs() < 1e-3);
+        assert!(gain_db(&fir, 200.0) < -40.0);
+    }
+
+    #[test]
+    fn invalid_designs_are_rejected() {
+        assert!(Iir::design(IirDesign::Butterworth, 0, Response::LowPass(100.0), SAMPLE_RATE).is_err());
+        assert!(Iir::design(IirDesign::Butterworth, 2, Response::LowPass(600.0), SAMPLE_RATE).is_err());
+        assert!(Iir::design(IirDesign::ChebyshevI(0.0), 2, Response::LowPass(100.0), SAMPLE_RATE).is_err());
+        assert!(Iir::design(IirDesign::Butterworth, 2, Response::BandPass(200.0, 100.0), SAMPLE_RATE).is_err());
+    }
+
+    #[test]
+    fn resample_poly_round_trips_slow_signals() {
+        let signal = |x: f64| (2.0 * PI * x / 40.0).sin() + 0.3 * (2.0 * PI * x / 17.0).cos();
+        let data: Vec<f64> = (0..300).map(|i| signal(i as f64)).collect();
+
+        // 3/2 then 2/3 restores the signal away from the zero-extended edges, within the pass-band ripple of the Kaiser design
+        let up = resample_poly(&data, 3, 2).unwrap();
+        assert_eq!(up.len(), 450);
+        for (i, &value) in up.iter().enumerate().skip(60).take(300) {
+            assert!((value - signal(i as f64 * 2.0 / 3.0)).abs() < 5e-3, "sample {}", i);
+        }
+        let back = resample_poly(&up, 2, 3).unwrap();
+        assert_eq!(back.len(), 300);
+        for i in 40..260 {
+            assert!((back[i] - data[i]).abs() < 5e-3, "sample {}", i);
+        }
+
+        // Common factors are reduced, equal factors copy, odd lengths round up
+        assert_eq!(resample_poly(&data, 4, 4).unwrap(), data);
+        assert_eq!(resample_poly(&data[..7], 1, 2).unwrap().len(), 4);
+        assert_eq!(resample_poly(&data, 6, 4).unwrap(), up);
+        assert!(resample_poly(&data, 0, 1).is_err());
+        assert!(resample_poly::<f64>(&[], 1, 2).is_err());
+    }
+}
@@ -0,0 +1,237 @@
+#![allow(dead_code)]
+
+/// Two-dimensional Discrete Fourier Transform for images and other width x height grids.
+///
+/// The transform is computed row-column: a 1D unitary FFT over every row, then over every column,
+/// so the overall scaling is 1/sqrt(width * height). Data is stored in row-major order.
+use crate::math::{self, Complex, Real};
+
+pub struct Fourier2D<T: Real = f32> {
+    width: usize,
+    height: usize,
+    original: Vec<T>,
+    // DFT coefficients in row-major order (ky * width + kx), zero frequency at index 0.
+    transform: Vec<Complex<T>>,
+}
+
+impl<T: Real> Fourier2D<T> {
+    /// Constructs a 2D Fourier object from real values in row-major order.
+    /// Returns an error if the dimensions do not match the data or it contains invalid values.
+    pub fn from_real(width: usize, height: usize, data: Vec<T>) -> Result<Self, String> {
+        // Validate input
+        if width == 0 || height == 0 { return Err("Image dimensions must be positive".to_string()); }
+        if data.len() != width * height {
+            return Err(format!("Expected {} values for a {}x{} image, got {}", width * height, width, height, data.len()));
+        }
+        // Check for NaN or Inf values
+        if data.iter().any(|val| !val.is_finite()) {
+            return Err("Input data contains invalid values (NaN or Inf)".to_string());
+        }
+
+        let mut transform: Vec<Complex<T>> = data.iter().map(|&x| Complex::new(x, T::zero())).collect();
+        fft2d(&mut transform, width, height, false);
+        Ok(Fourier2D { width, height, original: data, transform })
+    }
+
+    /// Constructs a 2D Fourier object from 8-bit grayscale pixels, scaled to [0, 1].
+    pub fn from_grayscale(width: usize, height: usize, pixels: &[u8]) -> Result<Self, String> {
+        let data = pixels.iter().map(|&p| math::cast(p as f64 / 255.0)).collect();
+        Self::from_real(width, height, data)
+    }
+
+    /// Constructs a 2D Fourier object from 8-bit RGBA pixels, using their luminance in [0, 1].
+    /// The alpha channel is ignored.
+    pub fn from_rgba(width: usize, height: usize, pixels: &[u8]) -> Result<Self, String> {
+        if pixels.len() != 4 * width * height {
+            return Err(format!("Expected {} bytes for a {}x{} RGBA image, got {}", 4 * width * height, width, height, pixels.len()));
+        }
+        let data = pixels.chunks_exact(4)
+            .map(|p| math::cast((0.299 * p[0] as f64 + 0.587 * p[1] as f64 + 0.114 * p[2] as f64) / 255.0))
+            .collect();
+        Self::from_real(width, height, data)
+    }
+
+    /// Returns the magnitude |X| of every coefficient.
+    /// If `shifted` is true, the output is fftshifted (zero frequency at the center).
+    pub fn magnitude_spectrum(&self, shifted: bool) -> Vec<T> {
+        let values = self.transform.iter().map(|c| c.norm()).collect();
+        if shifted { self.fftshift(values) } else { values }
+    }
+
+    /// Returns log(1 + |X|), which compresses the dynamic range for display.
+    /// If `shifted` is true, the output is fftshifted (zero frequency at the center).
+    pub fn log_magnitude_spectrum(&self, shifted: bool) -> Vec<T> {
+        let values = self.transform.iter().map(|c| c.norm().ln_1p()).collect();
+        if shifted { self.fftshift(values) } else { values }
+    }
+
+    /// Returns the phase (angle in radians) of every coefficient.
+    /// If `shifted` is true, the output is fftshifted (zero frequency at the center).
+    pub fn phase_spectrum(&self, shifted: bool) -> Vec<T> {
+        let values = self.transform.iter().map(|c| c.arg()).collect();
+        if shifted { self.fftshift(values) } else { values }
+    }
+
+    /// Reconstructs the image keeping only the frequencies that pass the mask.
+    /// Returns an error if the mask parameters are invalid.
+    pub fn filtered(&self, mask: Mask2D) -> Result<Vec<T>, String> {
+        mask.validate()?;
+        let mut masked = self.masked_transform(mask);
+        fft2d(&mut masked, self.width, self.height, true);
+        Ok(masked.iter().map(|c| c.re).collect())
+    }
+
+    /// Returns log(1 + |X|) after applying the mask, fftshifted for display.
+    pub fn masked_log_magnitude_spectrum(&self, mask: Mask2D) -> Result<Vec<T>, String> {
+        mask.validate()?;
+        let values = self.masked_transform(mask).iter().map(|c| c.norm().ln_1p()).collect();
+        Ok(self.fftshift(values))
+    }
+
+    /// Returns the image width.
+    pub fn width(&self) -> usize { self.width }
+    /// Returns the image height.
+    pub fn height(&self) -> usize { self.height }
+    /// Returns a reference to the original image data.
+    pub fn original(&self) -> &[T] { &self.original }
+
+    /// Zeroes every coefficient outside the mask.
+    fn masked_transform(&self, mask: Mask2D) -> Vec<Complex<T>> {
+        let mut masked = self.transform.clone();
+        for ky in 0..self.height {
+            let fy = signed_frequency(ky, self.height) as f64;
+            for kx in 0..self.width {
+                let fx = signed_frequency(kx, self.width) as f64;
+                if !mask.passes((fx * fx + fy * fy).sqrt()) {
+                    masked[ky * self.width + kx] = Complex::new(T::zero(), T::zero());
+                }
+            }
+        }
+        masked
+    }
+
+    /// Moves the zero frequency to the center (index width/2, height/2).
+    fn fftshift(&self, values: Vec<T>) -> Vec<T> {
+        let mut shifted = vec![T::zero(); values.len()];
+        for ky in 0..self.height {
+            let sy = (ky + self.height / 2) % self.height;
+            for kx in 0..self.width {
+                let sx = (kx + self.width / 2) % self.width;
+                shifted[sy * self.width + sx] = values[ky * self.width + kx];
+            }
+        }
+        shifted
+    }
+}
+
+/// Radial frequency masks for 2D filtering. Radii are distances from the zero frequency,
+/// in cycles per image (i.e. bin units).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mask2D {
+    /// Low-pass: keep frequencies with radius <= cutoff.
+    Low(f32),
+    /// High-pass: keep frequencies with radius > cutoff.
+    High(f32),
+    /// Band-pass: keep frequencies with inner <= radius <= outer.
+    Band(f32, f32),
+}
+
+impl Mask2D {
+    /// Returns an error if the radii are negative, not finite or out of order.
+    pub fn validate(&self) -> Result<(), String> {
+        let valid = |r: f32| r.is_finite() && r >= 0.0;
+        match *self {
+            Mask2D::Low(r) | Mask2D::High(r) if !valid(r) => {
+                Err(format!("Mask radius must be a non-negative number, got {}", r))
+            },
+            Mask2D::Band(inner, outer) if !valid(inner) || !valid(outer) || inner > outer => {
+                Err(format!("Invalid band-pass radii [{}, {}]", inner, outer))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns true if a frequency at the given radius is kept by the mask.
+    pub fn passes(&self, radius: f64) -> bool {
+        match *self {
+            Mask2D::Low(cutoff) => radius <= cutoff as f64,
+            Mask2D::High(cutoff) => radius > cutoff as f64,
+            Mask2D::Band(inner, outer) => radius >= inner as f64 && radius <= outer as f64,
+        }
+    }
+}
+
+/// Maps a DFT index in 0..n to its signed frequency (N-k ~ -k).
+fn signed_frequency(k: usize, n: usize) -> isize {
+    if k < n.div_ceil(2) { k as isize } else { k as isize - n as isize }
+}
+
+/// In-place unitary 2D FFT: transforms every row, then every column.
+fn fft2d<T: Real>(data: &mut [Complex<T>], width: usize, height: usize, inverse: bool) {
+    for row in data.chunks_exact_mut(width) {
+        let transformed = math::fft(row, inverse);
+        row.copy_from_slice(&transformed);
+    }
+
+    let mut column = Vec::with_capacity(height);
+    for x in 0..width {
+        column.clear();
+        column.extend((0..height).map(|y| data[y * width + x]));
+        for (y, val) in math::fft(&column, inverse).into_iter().enumerate() {
+            data[y * width + x] = val;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Non-square dimensions (one of them prime) so rows and columns take different FFT paths.
+    const WIDTH: usize = 12;
+    const HEIGHT: usize = 7;
+
+    fn test_image() -> Vec<f64> {
+        (0..WIDTH * HEIGHT).map(|i| {
+            let (x, y) = ((i % WIDTH) as f64, (i / WIDTH) as f64);
+            (0.7 * x).sin() + (1.3 * y).cos() + 0.05 * x * y
+        }).collect()
+    }
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (i, (a, e)) in actual.iter().zip(expected).enumerate() {
+            assert!((a - e).abs() < 1e-9, "pixel {}: {} != {}", i, a, e);
+        }
+    }
+
+    #[test]
+    fn all_pass_masks_reconstruct_the_image() {
+        let image = test_image();
+        let fourier = Fourier2D::from_real(WIDTH, HEIGHT, image.clone()).unwrap();
+        for mask in [Mask2D::Low(1e6), Mask2D::Band(0.0, 1e6)] {
+            assert_close(&fourier.filtered(mask).unwrap(), &image);
+        }
+    }
+
+    #[test]
+    fn zero_radius_masks_split_off_the_mean() {
+        let image = test_image();
+        let mean = image.iter().sum::<f64>() / image.len() as f64;
+        let fourier = Fourier2D::from_real(WIDTH, HEIGHT, image.clone()).unwrap();
+        assert_close(&fourier.filtered(Mask2D::Low(0.0)).unwrap(), &vec![mean; WIDTH * HEIGHT]);
+        let detrended: Vec<f64> = image.iter().map(|v| v - mean).collect();
+        assert_close(&fourier.filtered(Mask2D::High(0.0)).unwrap(), &detrended);
+    }
+
+    #[test]
+    fn invalid_radii_are_rejected() {
+        let fourier = Fourier2D::from_real(WIDTH, HEIGHT, test_image()).unwrap();
+        for mask in [Mask2D::Low(-1.0), Mask2D::High(f32::NAN), Mask2D::Band(-1.0, 2.0), Mask2D::Band(3.0, 2.0)] {
+            assert!(mask.validate().is_err(), "{:?}", mask);
+            assert!(fourier.filtered(mask).is_err(), "{:?}", mask);
+            assert!(fourier.masked_log_magnitude_spectrum(mask).is_err(), "{:?}", mask);
+        }
+        assert!(Mask2D::Band(2.0, 2.0).validate().is_ok());
+    }
+}
@@ -6,22 +6,220 @@ use web_canvas::console;
 mod math;
 mod plotter;
 mod animation;
+mod fourier2d;
+mod filter;
+mod wavelet;
+mod noise;
+mod csv;
+mod wav;
+mod expr;
+
+/// Sample rate (in Hz) of the example signals.
+const EXAMPLE_SAMPLE_RATE: f32 = 100.0;
+/// Number of frequencies evaluated by the zoom FFT over the significant band of the spectrum.
+const ZOOM_SPECTRUM_POINTS: usize = 1000;
+/// Shortest frame length and hop size (in samples) of the example spectrogram.
+const SPECTROGRAM_FRAME: usize = 64;
+const SPECTROGRAM_HOP: usize = 8;
+/// Dynamic range (in dB below the maximum) shown in the spectrogram.
+const SPECTROGRAM_RANGE_DB: f32 = 80.0;
+/// Minimum prominence of annotated spectrum peaks, relative to the highest power.
+const PEAK_MIN_PROMINENCE: f32 = 0.01;
+/// Segment length (in samples) of the averaged power spectral density estimates.
+const PSD_SEGMENT: usize = 100;
+/// Window used to measure the quality (SNR, THD, ...) of the example signals; its low leakage keeps the
+/// tone out of the noise bins.
+const QUALITY_WINDOW: math::Window = math::Window::Blackman;
+/// Highest harmonic of the fundamental counted as distortion rather than noise.
+const QUALITY_MAX_HARMONIC: usize = 9;
+/// Lowest autocorrelation peak, relative to the zero lag, for the example signal to count as periodic.
+const PERIOD_MIN_CORRELATION: f32 = 0.3;
+/// Most points drawn per waveform line or scalogram row; longer signals (e.g. audio clips) are decimated for display.
+const MAX_DISPLAY_POINTS: usize = 2000;
+/// Deepest level of the example scalogram (fewer if the wavelet is too long for the signal).
+const SCALOGRAM_LEVELS: usize = 6;
 
 struct ExampleCache {
-    kind: u32,
+    source: SignalSource,
     t: Vec<f32>,
     fourier: math::Fourier,
+    // Real part of the filtered signal of the last plot, for `export_wav`
+    filtered: Vec<f32>,
+    // Quality of the signal and of `filtered`, for `example_quality`
+    quality: [Option<math::SignalQuality>; 2],
+    // Period in seconds estimated from the autocorrelation, for `example_period`
+    period: Option<f32>,
+}
+
+/// Origin of the cached example signal.
+#[derive(Clone, Copy, PartialEq)]
+enum SignalSource {
+    /// One of the built-in generators, by `plot_example` kind
+    Generated(u32),
+    /// Samples passed by JS to `plot_signal`, read by `load_csv` or `load_wav`, or evaluated by `plot_expression`
+    User,
+}
+
+/// How the example spectrum is estimated.
+#[derive(Clone, Copy, PartialEq)]
+enum SpectrumEstimator {
+    /// Power spectrum of the whole signal
+    Periodogram,
+    /// Averaged periodograms of half-overlapping, windowed segments
+    Welch,
+    /// Averaged periodograms of non-overlapping, rectangular segments
+    Bartlett,
+}
+
+/// What the phase panel below a spectrum shows.
+#[derive(Clone, Copy, PartialEq)]
+enum PhaseView {
+    Hidden,
+    /// Phase wrapped to (-pi, pi]
+    Wrapped,
+    /// Phase unwrapped across the significant bins
+    Unwrapped,
+    /// Group delay in samples
+    GroupDelay,
+}
+
+/// Shape of the spectral mask built from the example frequency range [k_min, k_max].
+#[derive(Clone, Copy, PartialEq)]
+enum RangeShape {
+    /// Brick-wall band
+    Sharp,
+    /// Band edges tapered over the given number of bins
+    RaisedCosine(f32),
+    /// Butterworth-shaped band of the given order
+    Butterworth(u32),
+    /// Gaussian centered on the range, with half its width as standard deviation
+    Gaussian,
+    /// Gaussian notch removing the range
+    Notch,
+}
+
+impl RangeShape {
+    fn mask(&self, k_min: usize, k_max: usize) -> math::SpectralMask {
+        let (low, high) = (k_min as f32, k_max as f32);
+        let (center, half_width) = (0.5 * (low + high), (0.5 * (high - low)).max(0.5));
+        match *self {
+            RangeShape::Sharp => math::SpectralMask::Range(k_min, k_max),
+            RangeShape::RaisedCosine(taper) => math::SpectralMask::RaisedCosine(low, high, taper),
+            RangeShape::Butterworth(order) => math::SpectralMask::Butterworth(low, high, order),
+            RangeShape::Gaussian => math::SpectralMask::Gaussian(center, half_width),
+            RangeShape::Notch => math::SpectralMask::Notch(center, half_width),
+        }
+    }
+}
+
+/// Basis in which the example frequency range is kept.
+#[derive(Clone, Copy, PartialEq)]
+enum ExampleBasis {
+    /// DFT bins (periodic extension)
+    Fourier,
+    /// DCT-II coefficients (even extension, no jump at the edges)
+    Cosine,
+}
+
+/// Noise added to the example signals.
+#[derive(Clone, Copy)]
+struct ExampleNoise {
+    kind: noise::NoiseKind,
+    snr_db: f32,
+    seed: u64,
+}
+
+/// Time-domain filter overlaid on the example signal instead of the bin-range filter.
+/// It is designed for the sample rate of the signal when the signal is plotted.
+struct ExampleFilter {
+    design: ExampleDesign,
+    response: filter::Response,
+    order: usize,
+    zero_phase: bool,
+}
+
+/// Filter family of an `ExampleFilter`.
+#[derive(Clone, Copy, PartialEq)]
+enum ExampleDesign {
+    /// Windowed-sinc FIR (Hamming) with order + 1 taps
+    Fir,
+    Iir(filter::IirDesign),
+}
+
+impl ExampleFilter {
+    fn build(&self, sample_rate: f32) -> Result<Box<dyn filter::Filter<f32>>, String> {
+        match self.design {
+            ExampleDesign::Fir => filter::Fir::windowed_sinc(self.order + 1, self.response, math::Window::Hamming, sample_rate).map(|f| Box::new(f) as _),
+            ExampleDesign::Iir(design) => filter::Iir::design(design, self.order, self.response, sample_rate).map(|f| Box::new(f) as _),
+        }
+    }
 }
 
 thread_local! {
     // Cache for example data, shared across the application
     static EXAMPLE_CACHE: RefCell<Option<ExampleCache>> = RefCell::new(None);
+    // Window applied to the example signal before computing its spectrum
+    static SPECTRUM_WINDOW: RefCell<math::Window> = RefCell::new(math::Window::Rectangular);
+    // Zero-padding factor for the example spectrum (1 means no padding)
+    static SPECTRUM_PADDING: RefCell<usize> = RefCell::new(1);
+    // Number of peaks annotated on the example spectrum (0 disables the annotations)
+    static SPECTRUM_PEAKS: RefCell<usize> = RefCell::new(0);
+    // Content of the phase panels below the example and animation spectra
+    static SPECTRUM_PHASE: RefCell<PhaseView> = RefCell::new(PhaseView::Hidden);
+    // Estimator used for the example spectrum
+    static SPECTRUM_ESTIMATOR: RefCell<SpectrumEstimator> = RefCell::new(SpectrumEstimator::Periodogram);
+    // Shape of the example frequency-range filter
+    static EXAMPLE_RANGE_SHAPE: RefCell<RangeShape> = RefCell::new(RangeShape::Sharp);
+    // Whether the envelope of the example signal is overlaid on it
+    static EXAMPLE_ENVELOPE: RefCell<bool> = RefCell::new(false);
+    // Time-domain filter for the example signal (None uses the bin-range filter)
+    static EXAMPLE_FILTER: RefCell<Option<ExampleFilter>> = RefCell::new(None);
+    // Basis of the example frequency-range filter
+    static EXAMPLE_BASIS: RefCell<ExampleBasis> = RefCell::new(ExampleBasis::Fourier);
+    // Noise added to the example signals (None keeps them clean)
+    static EXAMPLE_NOISE: RefCell<Option<ExampleNoise>> = RefCell::new(None);
+    // WAV file of the last `export_wav` (see `export_wav_ptr`)
+    static EXPORT_WAV: RefCell<Vec<u8>> = RefCell::new(Vec::new());
+    // Message of the last error reported to JS (see `last_error_ptr`)
+    static LAST_ERROR: RefCell<String> = RefCell::new(String::new());
+    // Wavelet of the example scalogram
+    static SCALOGRAM_WAVELET: RefCell<wavelet::Wavelet> = RefCell::new(wavelet::Wavelet::Haar);
+    // Transform of the last image loaded from JS
+    static IMAGE_CACHE: RefCell<Option<fourier2d::Fourier2D>> = RefCell::new(None);
 }
 
 ///////////////////////////////////////////////////////////////////////////////
 /// Example data
 ///////////////////////////////////////////////////////////////////////////////
 
+/// Plot the phase (or group delay) of the bins with significant power in a panel below a spectrum,
+/// with frequencies multiplied by `scale` and the x-axis range of the magnitude panel.
+fn plot_phase_panel(canvas_name: &str, fourier: &math::Fourier, scale: f32, x_range: (f32, f32)) {
+    let view = SPECTRUM_PHASE.with(|cell| *cell.borrow());
+    if view == PhaseView::Hidden { return; }
+
+    // Phase is meaningless where there is no power, so only the bins shown in the spectrum are kept
+    let (freq, power) = fourier.power_spectrum(true);
+    let max_power = power.iter().fold(0.0f32, |acc, &x| acc.max(x));
+    let significant: Vec<usize> = (0..power.len()).filter(|&i| power[i] >= max_power * 0.001).collect();
+    let select = |values: &[f32]| -> Vec<f32> { significant.iter().map(|&i| values[i]).collect() };
+
+    let values = match view {
+        PhaseView::GroupDelay => select(&fourier.group_delay(true).1),
+        PhaseView::Unwrapped => math::unwrap_phase(&select(&fourier.phase_spectrum(true, false).1)),
+        _ => select(&fourier.phase_spectrum(true, false).1),
+    };
+    let x: Vec<f32> = significant.iter().map(|&i| freq[i] * scale).collect();
+
+    let plt = plotter::Plotter::get_or_create(canvas_name);
+    if let Err(msg) = plt.plot_histogram(&x, &values, canvas::TAB_OLIVE, scale) {
+        console::error(&format!("Error plotting phase: {}", msg));
+        return;
+    }
+    plt.set_x_range(x_range.0, x_range.1);
+    plt.show();
+}
+
 /// Find the frequency range where power is above a threshold
 fn find_significant_frequency_range(freq: &[f32], power: &[f32]) -> (f32, f32) {
     let max_power = power.iter().fold(0.0f32, |acc, &x| acc.max(x));
@@ -47,95 +245,744 @@ fn find_significant_frequency_range(freq: &[f32], power: &[f32]) -> (f32, f32) {
     (min_freq, max_freq)
 }
 
-fn generate_cache(kind: u32) -> ExampleCache {
+/// Reduce a line to at most `max_points` points for display, keeping the minimum and maximum of every bucket of
+/// samples (in their original order) so that peaks stay visible. Shorter lines are returned unchanged.
+fn decimate_for_display(x: &[f32], y: &[f32], max_points: usize) -> (Vec<f32>, Vec<f32>) {
+    if x.len() <= max_points { return (x.to_vec(), y.to_vec()); }
+    let bucket = x.len().div_ceil(max_points / 2);
+    let mut indices = Vec::with_capacity(max_points);
+    for start in (0..x.len()).step_by(bucket) {
+        let end = (start + bucket).min(x.len());
+        let by_value = |&a: &usize, &b: &usize| y[a].total_cmp(&y[b]);
+        let low = (start..end).min_by(by_value).unwrap_or(start);
+        let high = (start..end).max_by(by_value).unwrap_or(start);
+        indices.push(low.min(high));
+        if low != high { indices.push(low.max(high)); }
+    }
+    (indices.iter().map(|&i| x[i]).collect(), indices.iter().map(|&i| y[i]).collect())
+}
+
+/// Log an error to the console and keep it for JS to read with `last_error_ptr` and `last_error_len`.
+fn report_error(msg: &str) {
+    console::error(msg);
+    LAST_ERROR.with(|cell| { *cell.borrow_mut() = msg.to_string(); });
+}
+
+/// Build the cache of a signal sampled at `sample_rate` Hz.
+/// Returns an error if the samples or the sample rate are invalid, or there are fewer than 2 samples.
+fn build_cache(source: SignalSource, x: Vec<f32>, sample_rate: f32) -> Result<ExampleCache, String> {
+    let fourier = math::Fourier::from_real(x).and_then(|fourier| fourier.with_sample_rate(sample_rate))?;
+    cache_from_fourier(source, fourier)
+}
+
+/// Build the cache of an already transformed signal, with times from its sample rate.
+/// Returns an error if the signal has fewer than 2 samples, which cannot be plotted or filtered.
+fn cache_from_fourier(source: SignalSource, fourier: math::Fourier) -> Result<ExampleCache, String> {
+    if fourier.size() < 2 { return Err(format!("At least 2 samples are required, got {}", fourier.size())); }
+    let sample_rate = fourier.sample_rate().unwrap_or(EXAMPLE_SAMPLE_RATE);
+    let t = (0..fourier.size()).map(|i| i as f32 / sample_rate).collect();
+    let original_real: Vec<f32> = fourier.original().iter().map(|c| c.re).collect();
+    let period = math::estimate_period(&original_real, PERIOD_MIN_CORRELATION)
+        .ok()
+        .flatten()
+        .map(|samples| samples / sample_rate);
+    let quality = [measure_quality(original_real), None];
+    Ok(ExampleCache { source, t, fourier, filtered: Vec::new(), quality, period })
+}
+
+/// Measure the quality metrics of a signal, or None if they cannot be measured (e.g. no fundamental is found).
+fn measure_quality(data: Vec<f32>) -> Option<math::SignalQuality> {
+    math::Fourier::from_real_windowed(data, QUALITY_WINDOW)
+        .and_then(|fourier| fourier.signal_quality(QUALITY_MAX_HARMONIC))
+        .ok()
+}
+
+fn generate_cache(kind: u32) -> Result<ExampleCache, String> {
     const TOTAL_NUM_POINTS: usize = 500;
-    let mut t = Vec::with_capacity(TOTAL_NUM_POINTS);
     let mut x = Vec::with_capacity(TOTAL_NUM_POINTS);
 
     let generator: fn(f32, usize) -> f32 = match kind {
         /* Step function */ 0 => |_, i|  { if i > 150 && i < 350  { 1.0 } else { 0.0 }                             },
         /* Sine */          1 => |ti, _| { (2.0 * std::f32::consts::PI * ti).sin()                                 },
         /* Square */        2 => |ti, _| { if (2.0 * std::f32::consts::PI * ti).sin() >= 0.0 { 1.0 } else { -1.0 } },
+        /* Chirp */         4 => |ti, _| { (2.0 * std::f32::consts::PI * (0.5 * ti + 0.8 * ti * ti)).sin()         },
         /* Triangle */      _ => |ti, _| { 2.0 * (2.0 * (ti - (ti + 0.25).floor() + 0.25)).abs() - 1.0             },
     };
 
     for i in 0..TOTAL_NUM_POINTS {
-        let ti = i as f32 / 100.0;
-        t.push(ti);
+        let ti = i as f32 / EXAMPLE_SAMPLE_RATE;
         x.push(generator(ti, i));
     }
-
-    let fourier = match math::Fourier::from_real(x) {
-        Ok(fourier) => fourier,
-        Err(msg) => {
-            console::error(&format!("Failed to create Fourier instance: {}", msg));
-            panic!("Fourier creation failed");
+    if let Some(example_noise) = EXAMPLE_NOISE.with(|cell| *cell.borrow()) {
+        match noise::add_noise(&x, example_noise.kind, example_noise.snr_db, example_noise.seed) {
+            Ok(noisy) => x = noisy,
+            Err(msg) => console::error(&format!("Failed to add noise: {}", msg)),
         }
-    };
-    ExampleCache { kind, t, fourier }
+    }
+
+    build_cache(SignalSource::Generated(kind), x, EXAMPLE_SAMPLE_RATE)
 }
 
-fn plot_cached_example(k_min: usize, k_max: usize, cache: &mut ExampleCache) {
+fn plot_cached_example(k_min: usize, k_max: usize, cache: &mut ExampleCache) -> Result<(), String> {
     // Start with the example plotter
     let plt = plotter::Plotter::get_or_create("example-canvas");
-    let filtered = match cache.fourier.filtered_range(k_min, k_max) {
-        Ok(vec) => vec,
-        Err(msg) => { console::error(&format!("Error filtering: {}", msg)); return; }
-    };
 
     // Extract real parts for plotting
     let original_real: Vec<f32> = cache.fourier.original().iter().map(|c| c.re).collect();
-    let filtered_real: Vec<f32> = filtered.iter().map(|c| c.re).collect();
+    let sample_rate = cache.fourier.sample_rate().unwrap_or(EXAMPLE_SAMPLE_RATE);
+    let time_filtered = EXAMPLE_FILTER.with(|cell| {
+        cell.borrow().as_ref().map(|example_filter| {
+            let designed = example_filter.build(sample_rate)
+                .map_err(|msg| format!("Error designing filter: {}", msg))?;
+            Ok::<_, String>(if example_filter.zero_phase { filter::filtfilt(designed.as_ref(), &original_real) }
+                            else { designed.filter(&original_real) })
+        })
+    }).transpose()?;
+    let filtered_real: Vec<f32> = match time_filtered {
+        Some(vec) => vec,
+        None => {
+            // The sharp range keeps the bounds checks of filtered_range; smooth shapes may extend past it
+            let shape = EXAMPLE_RANGE_SHAPE.with(|cell| *cell.borrow());
+            let result = match EXAMPLE_BASIS.with(|cell| *cell.borrow()) {
+                ExampleBasis::Fourier => match shape {
+                    RangeShape::Sharp => cache.fourier.filtered_range(k_min, k_max),
+                    _ => cache.fourier.filtered_with_mask(&shape.mask(k_min, k_max)),
+                }.map(|vec| vec.iter().map(|c| c.re).collect()),
+                // DCT-II coefficient j oscillates at about j/2 DFT bins, so double the range to keep the same band
+                ExampleBasis::Cosine => math::TrigTransform::from_real(original_real.clone(), math::TrigKind::DctII)
+                    .and_then(|cosine| match shape {
+                        RangeShape::Sharp => cosine.filtered_range(2 * k_min, 2 * k_max),
+                        _ => cosine.filtered_with_mask(&shape.mask(2 * k_min, 2 * k_max)),
+                    }),
+            };
+            result.map_err(|msg| format!("Error filtering: {}", msg))?
+        },
+    };
 
-    if let Err(msg) = plt.plot_line(&cache.t, &original_real, canvas::TAB_BLUE, 2.0) {
-        console::error(&format!("Error plotting function: {}", msg));
-        return;
-    }
-    if let Err(msg) = plt.plot_line(&cache.t, &filtered_real, canvas::TAB_ORANGE, 2.0) {
-        console::error(&format!("Error plotting filtered: {}", msg));
-        return;
+    // The quality of the filtered signal shows how much of the noise the filter removed
+    // (that of the original signal is measured once, when the cache is built)
+    cache.quality[1] = measure_quality(filtered_real.clone());
+    cache.filtered = filtered_real;
+
+    let (t, original_real) = decimate_for_display(&cache.t, &original_real, MAX_DISPLAY_POINTS);
+    plt.plot_line(&t, &original_real, canvas::TAB_BLUE, 2.0)
+        .map_err(|msg| format!("Error plotting function: {}", msg))?;
+    let (t, filtered_real) = decimate_for_display(&cache.t, &cache.filtered, MAX_DISPLAY_POINTS);
+    plt.plot_line(&t, &filtered_real, canvas::TAB_ORANGE, 2.0)
+        .map_err(|msg| format!("Error plotting filtered: {}", msg))?;
+    if EXAMPLE_ENVELOPE.with(|cell| *cell.borrow()) {
+        // Envelope of the original signal, from the magnitude of its analytic signal
+        let envelope = math::envelope(&cache.fourier.analytic_signal());
+        let (t, envelope) = decimate_for_display(&cache.t, &envelope, MAX_DISPLAY_POINTS);
+        plt.plot_line(&t, &envelope, canvas::TAB_RED, 1.0)
+            .map_err(|msg| format!("Error plotting envelope: {}", msg))?;
     }
     plt.show();
+    Ok(())
 }
 
-fn plot_cached_spectrum(cache: &mut ExampleCache) {
-    // Now plot the Fourier spectrum
-    let (freq, power) = cache.fourier.power_spectrum(true);
+/// Plot the spectrum of the cached signal with the selected estimator.
+/// Complex signals keep their imaginary part, so their spectrum covers negative frequencies too.
+fn plot_cached_spectrum(cache: &mut ExampleCache) -> Result<(), String> {
+    let estimator = SPECTRUM_ESTIMATOR.with(|cell| *cell.borrow());
+    if estimator != SpectrumEstimator::Periodogram {
+        return plot_cached_density(cache, estimator);
+    }
+
+    // The spectrum may use a windowed and padded transform, while filtering keeps the plain one
+    let window = SPECTRUM_WINDOW.with(|cell| *cell.borrow());
+    let padding_factor = SPECTRUM_PADDING.with(|cell| *cell.borrow());
+    let padding = if padding_factor > 1 { math::Padding::ToLength(padding_factor * cache.fourier.size()) } else { math::Padding::None };
+    let sample_rate = cache.fourier.sample_rate().unwrap_or(1.0);
+
+    let spectral = if window == math::Window::Rectangular && padding == math::Padding::None { None } else {
+        let prepared = if cache.fourier.is_real() {
+            let data: Vec<f32> = cache.fourier.original().iter().map(|c| c.re).collect();
+            math::Fourier::from_real_windowed_padded(data, window, padding)
+        } else {
+            math::Fourier::from_complex_windowed_padded(cache.fourier.original().to_vec(), window, padding)
+        };
+        let fourier = prepared.and_then(|fourier| fourier.with_sample_rate(sample_rate))
+            .map_err(|msg| format!("Error preparing spectrum: {}", msg))?;
+        Some(fourier)
+    };
+    let fourier = spectral.as_ref().unwrap_or(&cache.fourier);
+
+    // Now plot the Fourier spectrum in Hz, so that padded and unpadded spectra share the same axis
+    let (freq, power) = fourier.power_spectrum_in(true, math::FrequencyUnit::Hertz)
+        .map_err(|msg| format!("Error computing power spectrum: {}", msg))?;
+    let resolution = fourier.frequency_resolution().unwrap_or(1.0);
 
     // Find the range where power is significant
     let (min_freq, max_freq) = find_significant_frequency_range(&freq, &power);
 
     let plt = plotter::Plotter::get_or_create("spectrum-canvas");
-    if let Err(msg) = plt.plot_histogram(&freq, &power, canvas::TAB_GREEN, 1.0) {
-        console::error(&format!("Error plotting power spectrum: {}", msg));
-        return;
+    plt.plot_histogram(&freq, &power, canvas::TAB_GREEN, resolution)
+        .map_err(|msg| format!("Error plotting power spectrum: {}", msg))?;
+
+    // Overlay a high-resolution view of the significant band computed with the zoom FFT
+    if min_freq < max_freq {
+        match fourier.zoom_spectrum(min_freq / resolution, max_freq / resolution, ZOOM_SPECTRUM_POINTS) {
+            Ok((mut zoom_freq, zoom_power)) => {
+                zoom_freq.iter_mut().for_each(|f| *f *= resolution);
+                if let Err(msg) = plt.plot_line(&zoom_freq, &zoom_power, canvas::TAB_RED, 1.0) {
+                    console::error(&format!("Error plotting zoomed spectrum: {}", msg));
+                }
+            },
+            Err(msg) => console::error(&format!("Error computing zoomed spectrum: {}", msg)),
+        }
+    }
+
+    // Mark the strongest peaks with their interpolated frequency; Quinn's estimator assumes no window
+    let num_peaks = SPECTRUM_PEAKS.with(|cell| *cell.borrow());
+    if num_peaks > 0 {
+        let max_power = power.iter().fold(0.0f32, |acc, &x| acc.max(x));
+        let interpolation = if window == math::Window::Rectangular { math::PeakInterpolation::Quinn } else { math::PeakInterpolation::Gaussian };
+        let peaks = fourier.spectral_peaks(num_peaks, max_power * PEAK_MIN_PROMINENCE, interpolation);
+        let peak_freq: Vec<f32> = peaks.iter().map(|peak| peak.frequency * resolution).collect();
+        let peak_power: Vec<f32> = peaks.iter().map(|peak| peak.power).collect();
+        let labels: Vec<String> = peak_freq.iter().map(|f| format!("{:.3} Hz", f)).collect();
+        if let Err(msg) = plt.plot_markers(&peak_freq, &peak_power, &labels, canvas::TAB_ORANGE) {
+            console::error(&format!("Error plotting spectrum peaks: {}", msg));
+        }
     }
 
     // Set the x-axis range to focus on significant frequencies
     plt.set_x_range(min_freq, max_freq);
 
     plt.show();
+    plot_phase_panel("spectrum-phase-canvas", fourier, resolution, (min_freq, max_freq));
+    Ok(())
+}
+
+/// Returns the samples of a real cached signal, or an error naming the view that does not support complex ones.
+fn real_samples(cache: &ExampleCache, view: &str) -> Result<Vec<f32>, String> {
+    if !cache.fourier.is_real() {
+        return Err(format!("The {} is only available for real signals", view));
+    }
+    Ok(cache.fourier.original().iter().map(|c| c.re).collect())
+}
+
+fn plot_cached_density(cache: &mut ExampleCache, estimator: SpectrumEstimator) -> Result<(), String> {
+    let data = real_samples(cache, "power spectral density")?;
+    let sample_rate = cache.fourier.sample_rate().unwrap_or(1.0);
+    let result = match estimator {
+        SpectrumEstimator::Bartlett => math::bartlett(&data, sample_rate, PSD_SEGMENT, math::SpectrumSides::TwoSided),
+        _ => {
+            let window = SPECTRUM_WINDOW.with(|cell| *cell.borrow());
+            math::welch(&data, sample_rate, PSD_SEGMENT, PSD_SEGMENT / 2, window, math::SpectrumSides::TwoSided)
+        },
+    };
+    let (freq, density) = result.map_err(|msg| format!("Error estimating power spectral density: {}", msg))?;
+    let (min_freq, max_freq) = find_significant_frequency_range(&freq, &density);
+
+    let plt = plotter::Plotter::get_or_create("spectrum-canvas");
+    plt.plot_histogram(&freq, &density, canvas::TAB_GREEN, sample_rate / PSD_SEGMENT as f32)
+        .map_err(|msg| format!("Error plotting power spectral density: {}", msg))?;
+    plt.set_x_range(min_freq, max_freq);
+    plt.show();
+    Ok(())
+}
+
+fn plot_cached_spectrogram(cache: &mut ExampleCache) -> Result<(), String> {
+    let data = real_samples(cache, "spectrogram")?;
+    // Long signals use longer hops, and frames at least as long, so that the number of frames stays displayable
+    let hop = SPECTROGRAM_HOP.max(data.len() / MAX_DISPLAY_POINTS);
+    let frame = SPECTROGRAM_FRAME.max(hop.next_power_of_two());
+    let stft = math::Stft::from_real(&data, frame, hop, math::Window::Hann)
+        .map_err(|msg| format!("Error computing spectrogram: {}", msg))?;
+
+    // Show power in dB, clipped to a fixed dynamic range below the maximum
+    let power = stft.power_spectrogram();
+    let max_power = power.iter().fold(0.0f32, |acc, &x| acc.max(x));
+    let floor = max_power * 10.0f32.powf(-SPECTROGRAM_RANGE_DB / 10.0);
+    let decibels: Vec<f32> = power.iter().map(|&p| 10.0 * p.max(floor).max(f32::MIN_POSITIVE).log10()).collect();
+
+    // Frame centers are sample indices and bins are cycles per frame; convert them with the sample rate
+    let sample_rate = cache.fourier.sample_rate().unwrap_or(1.0);
+    let times: Vec<f32> = stft.frame_times().iter().map(|&i| i / sample_rate).collect();
+    let bin_width = sample_rate / frame as f32;
+    let frequencies: Vec<f32> = stft.bin_frequencies().iter().map(|&k| k * bin_width).collect();
+
+    let plt = plotter::Plotter::get_or_create("spectrogram-canvas");
+    plt.plot_heatmap(&times, &frequencies, &decibels)
+        .map_err(|msg| format!("Error plotting spectrogram: {}", msg))?;
+    plt.show();
+    Ok(())
 }
 
+fn plot_cached_scalogram(cache: &mut ExampleCache) -> Result<(), String> {
+    let data = real_samples(cache, "scalogram")?;
+    let wavelet = SCALOGRAM_WAVELET.with(|cell| *cell.borrow());
+    let levels = SCALOGRAM_LEVELS.min(wavelet.max_level(data.len()));
+    let decomposition = wavelet::WaveletDecomposition::new(&data, wavelet, levels)
+        .map_err(|msg| format!("Error computing scalogram: {}", msg))?;
+
+    // One column per sample and one row per level, finest details (highest frequencies) at the bottom;
+    // long signals keep the largest magnitude of every bucket of columns
+    let rows: Vec<f32> = (1..=levels).map(|level| level as f32).collect();
+    let values = decomposition.scalogram();
+    let bucket = cache.t.len().div_ceil(MAX_DISPLAY_POINTS);
+    let times: Vec<f32> = cache.t.chunks(bucket).map(|chunk| chunk[chunk.len() / 2]).collect();
+    let values: Vec<f32> = values.chunks(bucket * levels)
+        .flat_map(|columns| (0..levels).map(move |j| columns.iter().skip(j).step_by(levels).fold(0.0f32, |acc, &x| acc.max(x))))
+        .collect();
+    let plt = plotter::Plotter::get_or_create("scalogram-canvas");
+    plt.plot_heatmap(&times, &rows, &values)
+        .map_err(|msg| format!("Error plotting scalogram: {}", msg))?;
+    plt.show();
+    Ok(())
+}
+
+/// Plot a new signal with all its views and keep it as the cached example, even if the plot fails.
+/// Every view is attempted; the first error is returned.
+fn plot_new_cache(k_min: usize, k_max: usize, mut cache: ExampleCache) -> Result<(), String> {
+    let result = plot_cached_example(k_min, k_max, &mut cache);
+    let spectrum = plot_cached_spectrum(&mut cache);
+    let spectrogram = plot_cached_spectrogram(&mut cache);
+    let scalogram = plot_cached_scalogram(&mut cache);
+    EXAMPLE_CACHE.with(|cell| { *cell.borrow_mut() = Some(cache); });
+    result.and(spectrum).and(spectrogram).and(scalogram)
+}
+
+/// Redraw one view of the cached signal, if any, and report its error through the error channel.
+fn replot_cached_view(plot: fn(&mut ExampleCache) -> Result<(), String>) -> bool {
+    let result = EXAMPLE_CACHE.with(|cell| match cell.borrow_mut().as_mut() {
+        Some(cache) => plot(cache),
+        None => Ok(()),
+    });
+    result.map_err(|msg| report_error(&msg)).is_ok()
+}
+
+/// Plot a built-in example signal (kind: 0=step, 1=sine, 2=square, 3=triangle, 4=chirp) filtered to [k_min, k_max].
+/// Returns false on error, with the message available through `last_error_ptr`.
 #[no_mangle]
-pub fn plot_example(k_min: usize, k_max: usize, kind: u32) {
-    let mut cache_kind_match = false;
-    EXAMPLE_CACHE.with(|cell| {
-        // If the cache already exists and matches the kind, use it
-        if let Some(ref mut cache) = *cell.borrow_mut() {
-            if cache.kind == kind {
-                plot_cached_example(k_min, k_max, cache);
-                cache_kind_match = true;
-            }
+pub fn plot_example(k_min: usize, k_max: usize, kind: u32) -> bool {
+    let source = SignalSource::Generated(kind);
+    // If the cache already exists and matches the kind, use it
+    let cached = EXAMPLE_CACHE.with(|cell| {
+        cell.borrow_mut().as_mut()
+            .filter(|cache| cache.source == source)
+            .map(|cache| plot_cached_example(k_min, k_max, cache))
+    });
+
+    let result = cached.unwrap_or_else(|| {
+        generate_cache(kind)
+            .map_err(|msg| format!("Failed to create Fourier instance: {}", msg))
+            .and_then(|cache| plot_new_cache(k_min, k_max, cache))
+    });
+    result.map_err(|msg| report_error(&msg)).is_ok()
+}
+
+/// Replot the cached signal, built-in or loaded with `plot_signal`, filtered to [k_min, k_max].
+/// Returns false on error (or if no signal was plotted yet), with the message available through `last_error_ptr`.
+#[no_mangle]
+pub fn replot_example(k_min: usize, k_max: usize) -> bool {
+    let result = EXAMPLE_CACHE.with(|cell| {
+        match cell.borrow_mut().as_mut() {
+            Some(cache) => plot_cached_example(k_min, k_max, cache),
+            None => Err("No signal loaded".to_string()),
         }
     });
+    result.map_err(|msg| report_error(&msg)).is_ok()
+}
 
-    if cache_kind_match { return; }
+/// Allocate a buffer of `len` floats in WASM memory for JS to copy signal samples into.
+#[no_mangle]
+pub fn alloc_f32_buffer(len: usize) -> *mut f32 {
+    let mut buffer = Vec::<f32>::with_capacity(len);
+    let ptr = buffer.as_mut_ptr();
+    std::mem::forget(buffer);
+    ptr
+}
 
-    let mut cache = generate_cache(kind);
-    plot_cached_example(k_min, k_max, &mut cache);
-    plot_cached_spectrum(&mut cache);
-    EXAMPLE_CACHE.with(|cell| { *cell.borrow_mut() = Some(cache); });
+/// Release a buffer obtained from `alloc_f32_buffer`.
+///
+/// # Safety
+/// `ptr` and `len` must come from a single call to `alloc_f32_buffer`, and the buffer must not be used afterwards.
+#[no_mangle]
+pub unsafe fn free_buffer(ptr: *mut f32, len: usize) {
+    drop(Vec::from_raw_parts(ptr, 0, len));
+}
+
+/// Analyse caller-provided samples (taken at `sample_rate` Hz) like the built-in examples, filtered to [k_min, k_max].
+/// The samples are copied, so the buffer can be freed afterwards. Noise is not added to user signals.
+/// Returns false on error, with the message available through `last_error_ptr`.
+///
+/// # Safety
+/// `ptr` must point to `len` initialized floats, e.g. a buffer from `alloc_f32_buffer`.
+#[no_mangle]
+pub unsafe fn plot_signal(ptr: *const f32, len: usize, sample_rate: f32, k_min: usize, k_max: usize) -> bool {
+    let samples = if len == 0 { Vec::new() } else { std::slice::from_raw_parts(ptr, len).to_vec() };
+    let result = build_cache(SignalSource::User, samples, sample_rate)
+        .map_err(|msg| format!("Invalid signal: {}", msg))
+        .and_then(|cache| plot_new_cache(k_min, k_max, cache));
+    result.map_err(|msg| report_error(&msg)).is_ok()
+}
+
+/// Allocate a buffer of `len` bytes in WASM memory for JS to copy file contents, text or image pixels into.
+#[no_mangle]
+pub fn alloc_u8_buffer(len: usize) -> *mut u8 {
+    let mut buffer = Vec::<u8>::with_capacity(len);
+    let ptr = buffer.as_mut_ptr();
+    std::mem::forget(buffer);
+    ptr
+}
+
+/// Release a buffer obtained from `alloc_u8_buffer`.
+///
+/// # Safety
+/// `ptr` and `len` must come from a single call to `alloc_u8_buffer`, and the buffer must not be used afterwards.
+#[no_mangle]
+pub unsafe fn free_u8_buffer(ptr: *mut u8, len: usize) {
+    drop(Vec::from_raw_parts(ptr, 0, len));
+}
+
+/// Read a CSV/TSV file (raw bytes from a JS file input) and analyse one of its columns, filtered to [k_min, k_max].
+/// Columns are numbered from 1, with 0 meaning none: `time_column` (uniform times in seconds) sets the sample rate,
+/// otherwise `default_sample_rate` is used; `imaginary_column` makes the signal complex (only its real part is plotted).
+/// Returns false on error, with the message (including the file line for parse errors) available through `last_error_ptr`.
+///
+/// # Safety
+/// `ptr` must point to `len` initialized bytes, e.g. a buffer from `alloc_u8_buffer`.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe fn load_csv(ptr: *const u8, len: usize, time_column: u32, value_column: u32, imaginary_column: u32,
+                       default_sample_rate: f32, k_min: usize, k_max: usize) -> bool {
+    let bytes = if len == 0 { &[][..] } else { std::slice::from_raw_parts(ptr, len) };
+    let column = |number: u32| (number as usize).checked_sub(1);
+    let result = std::str::from_utf8(bytes)
+        .map_err(|_| "File is not valid UTF-8 text".to_string())
+        .and_then(csv::parse)
+        .and_then(|table| {
+            let value = column(value_column).ok_or_else(|| "A value column must be selected".to_string())?;
+            table.signal(csv::ColumnSelection { time: column(time_column), value, imaginary: column(imaginary_column) })
+        })
+        .and_then(|signal| signal.into_fourier(default_sample_rate))
+        .and_then(|fourier| cache_from_fourier(SignalSource::User, fourier))
+        .map_err(|msg| format!("Invalid CSV file: {}", msg))
+        .and_then(|cache| plot_new_cache(k_min, k_max, cache));
+    result.map_err(|msg| report_error(&msg)).is_ok()
+}
+
+/// Decode a WAV file (raw bytes from a JS file input) and analyse it at its own sample rate, filtered to [k_min, k_max].
+/// channel: 0=average of all channels, 1..=number of channels to select one (1 is left and 2 is right for stereo)
+/// Returns false on error, with the message available through `last_error_ptr`.
+///
+/// # Safety
+/// `ptr` must point to `len` initialized bytes, e.g. a buffer from `alloc_u8_buffer`.
+#[no_mangle]
+pub unsafe fn load_wav(ptr: *const u8, len: usize, channel: u32, k_min: usize, k_max: usize) -> bool {
+    let bytes = if len == 0 { &[][..] } else { std::slice::from_raw_parts(ptr, len) };
+    let result = wav::decode(bytes)
+        .and_then(|audio| {
+            let samples = match channel {
+                0 => audio.mixdown(),
+                _ => audio.channel(channel as usize - 1)
+                    .ok_or_else(|| format!("Channel {} does not exist (the file has {})", channel, audio.num_channels()))?
+                    .to_vec(),
+            };
+            build_cache(SignalSource::User, samples, audio.sample_rate() as f32)
+        })
+        .map_err(|msg| format!("Invalid WAV file: {}", msg))
+        .and_then(|cache| plot_new_cache(k_min, k_max, cache));
+    result.map_err(|msg| report_error(&msg)).is_ok()
+}
+
+/// Evaluate an expression of `t` (UTF-8 text, see `expr`) at `num_samples` times evenly spaced over [t_start, t_end)
+/// and analyse it like the built-in examples, filtered to [k_min, k_max].
+/// Returns false on error, with the message (including the position for syntax errors) available through `last_error_ptr`.
+///
+/// # Safety
+/// `ptr` must point to `len` initialized bytes, e.g. a buffer from `alloc_u8_buffer`.
+#[no_mangle]
+pub unsafe fn plot_expression(ptr: *const u8, len: usize, t_start: f32, t_end: f32, num_samples: usize,
+                              k_min: usize, k_max: usize) -> bool {
+    let bytes = if len == 0 { &[][..] } else { std::slice::from_raw_parts(ptr, len) };
+    let result = std::str::from_utf8(bytes)
+        .map_err(|_| "Expression is not valid UTF-8 text".to_string())
+        .and_then(expr::Expression::parse)
+        .and_then(|expression| expression.sample(t_start as f64, t_end as f64, num_samples))
+        .and_then(|(_, values)| {
+            let sample_rate = num_samples as f32 / (t_end - t_start);
+            let mut cache = build_cache(SignalSource::User, values.iter().map(|&x| x as f32).collect(), sample_rate)?;
+            // The time axis starts at t_start rather than 0
+            cache.t.iter_mut().for_each(|t| *t += t_start);
+            Ok(cache)
+        })
+        .map_err(|msg| format!("Invalid expression: {}", msg))
+        .and_then(|cache| plot_new_cache(k_min, k_max, cache));
+    result.map_err(|msg| report_error(&msg)).is_ok()
+}
+
+/// Encode the filtered signal of the last example plot as a mono WAV file at the signal's sample rate (rounded to 1 Hz),
+/// kept in WASM memory for JS to read with `export_wav_ptr` and `export_wav_len` until the next export.
+/// format: 0=16-bit PCM, 1=32-bit float
+/// normalize: scale the signal so its peak is at full scale (integer formats clip samples outside [-1, 1])
+/// Returns false on error, with the message available through `last_error_ptr`.
+#[no_mangle]
+pub fn export_wav(format: u32, normalize: bool) -> bool {
+    let format = match format {
+        0 => wav::SampleFormat::Pcm(16),
+        _ => wav::SampleFormat::Float32,
+    };
+    let result = EXAMPLE_CACHE.with(|cell| {
+        let cell = cell.borrow();
+        let cache = cell.as_ref().ok_or("No signal loaded")?;
+        let peak = cache.filtered.iter().fold(0.0f32, |acc, &x| acc.max(x.abs()));
+        let scale = if normalize && peak > 0.0 { 1.0 / peak } else { 1.0 };
+        let samples: Vec<f32> = cache.filtered.iter().map(|&x| x * scale).collect();
+        let sample_rate = cache.fourier.sample_rate().unwrap_or(EXAMPLE_SAMPLE_RATE).round().max(1.0) as u32;
+        wav::encode(&samples, sample_rate, format).map_err(|msg| format!("Failed to export WAV: {}", msg))
+    });
+    match result {
+        Ok(bytes) => { EXPORT_WAV.with(|cell| { *cell.borrow_mut() = bytes; }); true },
+        Err(msg) => { report_error(&msg); false },
+    }
+}
+
+/// Pointer to the bytes of the WAV file made by the last successful `export_wav`.
+#[no_mangle]
+pub fn export_wav_ptr() -> *const u8 {
+    EXPORT_WAV.with(|cell| cell.borrow().as_ptr())
+}
+
+/// Length in bytes of the exported WAV file (see `export_wav_ptr`).
+#[no_mangle]
+pub fn export_wav_len() -> usize {
+    EXPORT_WAV.with(|cell| cell.borrow().len())
+}
+
+/// Pointer to the UTF-8 bytes of the last error message reported by a function returning false.
+#[no_mangle]
+pub fn last_error_ptr() -> *const u8 {
+    LAST_ERROR.with(|cell| cell.borrow().as_ptr())
+}
+
+/// Length in bytes of the last error message (see `last_error_ptr`).
+#[no_mangle]
+pub fn last_error_len() -> usize {
+    LAST_ERROR.with(|cell| cell.borrow().len())
+}
+
+/// Select the wavelet of the example scalogram and redraw it.
+/// order: 1=Haar, 2..8=Daubechies with that many vanishing moments
+/// Returns false on error, with the message available through `last_error_ptr`.
+#[no_mangle]
+pub fn set_scalogram_wavelet(order: u32) -> bool {
+    let wavelet = if order == 1 { wavelet::Wavelet::Haar } else { wavelet::Wavelet::Daubechies(order) };
+    if let Err(msg) = wavelet.validate() {
+        report_error(&format!("Invalid wavelet: {}", msg));
+        return false;
+    }
+
+    SCALOGRAM_WAVELET.with(|cell| { *cell.borrow_mut() = wavelet; });
+    replot_cached_view(plot_cached_scalogram)
+}
+
+/// Select the window used for the example spectrum and redraw it.
+/// kind: 0=rectangular, 1=Hann, 2=Hamming, 3=Blackman, 4=Kaiser(beta), 5=flat-top, 6=Tukey(alpha)
+/// Returns false on error, with the message available through `last_error_ptr`.
+#[no_mangle]
+pub fn set_spectrum_window(kind: u32, parameter: f32) -> bool {
+    let window = match kind {
+        0 => math::Window::Rectangular,
+        1 => math::Window::Hann,
+        2 => math::Window::Hamming,
+        3 => math::Window::Blackman,
+        4 => math::Window::Kaiser(parameter),
+        5 => math::Window::FlatTop,
+        6 => math::Window::Tukey(parameter),
+        _ => { report_error(&format!("Unknown window code: {}", kind)); return false; }
+    };
+    if let Err(msg) = window.validate() {
+        report_error(&format!("Invalid window: {}", msg));
+        return false;
+    }
+
+    SPECTRUM_WINDOW.with(|cell| { *cell.borrow_mut() = window; });
+    replot_cached_view(plot_cached_spectrum)
+}
+
+/// Select the zero-padding factor for the example spectrum and redraw it.
+/// The signal is padded to `factor` times its length; 0 or 1 disables padding.
+/// Returns false on error, with the message available through `last_error_ptr`.
+#[no_mangle]
+pub fn set_spectrum_padding(factor: usize) -> bool {
+    SPECTRUM_PADDING.with(|cell| { *cell.borrow_mut() = factor.max(1); });
+    replot_cached_view(plot_cached_spectrum)
+}
+
+/// Select how many of the strongest spectrum peaks are marked and labelled (0 disables) and redraw it.
+/// Returns false on error, with the message available through `last_error_ptr`.
+#[no_mangle]
+pub fn set_spectrum_peaks(count: usize) -> bool {
+    SPECTRUM_PEAKS.with(|cell| { *cell.borrow_mut() = count; });
+    replot_cached_view(plot_cached_spectrum)
+}
+
+/// Select what the phase panels below the spectra show and redraw the example one.
+/// The animation panel follows on the next animation start.
+/// view: 0=hidden, 1=wrapped phase, 2=unwrapped phase, 3=group delay (samples)
+/// Returns false on error, with the message available through `last_error_ptr`.
+#[no_mangle]
+pub fn set_spectrum_phase(view: u32) -> bool {
+    let view = match view {
+        0 => PhaseView::Hidden,
+        1 => PhaseView::Wrapped,
+        2 => PhaseView::Unwrapped,
+        3 => PhaseView::GroupDelay,
+        _ => { report_error(&format!("Unknown phase view code: {}", view)); return false; }
+    };
+    SPECTRUM_PHASE.with(|cell| { *cell.borrow_mut() = view; });
+    replot_cached_view(plot_cached_spectrum)
+}
+
+/// Select how the example spectrum is estimated and redraw it.
+/// kind: 0=periodogram, 1=Welch (selected window, 50% overlap), 2=Bartlett
+/// Returns false on error, with the message available through `last_error_ptr`.
+#[no_mangle]
+pub fn set_spectrum_estimator(kind: u32) -> bool {
+    let estimator = match kind {
+        0 => SpectrumEstimator::Periodogram,
+        1 => SpectrumEstimator::Welch,
+        2 => SpectrumEstimator::Bartlett,
+        _ => { report_error(&format!("Unknown spectrum estimator code: {}", kind)); return false; }
+    };
+
+    SPECTRUM_ESTIMATOR.with(|cell| { *cell.borrow_mut() = estimator; });
+    replot_cached_view(plot_cached_spectrum)
+}
+
+/// Show or hide the envelope (magnitude of the analytic signal) over the example signal.
+/// It takes effect on the next `plot_example`.
+#[no_mangle]
+pub fn set_example_envelope(show: bool) {
+    EXAMPLE_ENVELOPE.with(|cell| { *cell.borrow_mut() = show; });
+}
+
+/// Select the shape of the mask built from the example frequency range; it takes effect on the next `plot_example`.
+/// kind: 0=brick-wall, 1=raised-cosine edges (parameter = taper in bins), 2=Butterworth (parameter = order),
+///       3=Gaussian centered on the range, 4=notch removing the range
+/// Returns false on error, with the message available through `last_error_ptr`.
+#[no_mangle]
+pub fn set_example_range_shape(kind: u32, parameter: f32) -> bool {
+    let shape = match kind {
+        0 => RangeShape::Sharp,
+        1 => RangeShape::RaisedCosine(parameter),
+        2 => RangeShape::Butterworth(parameter.round().max(0.0) as u32),
+        3 => RangeShape::Gaussian,
+        4 => RangeShape::Notch,
+        _ => { report_error(&format!("Unknown range shape code: {}", kind)); return false; }
+    };
+    if let Err(msg) = shape.mask(0, 1).validate() {
+        report_error(&format!("Invalid range shape: {}", msg));
+        return false;
+    }
+    EXAMPLE_RANGE_SHAPE.with(|cell| { *cell.borrow_mut() = shape; });
+    true
+}
+
+/// Select the noise added to the example signals; it takes effect on the next `plot_example`.
+/// kind: 0=none, 1=white Gaussian, 2=uniform, 3=pink (1/f), 4=brown (1/f²)
+/// The noise is scaled to the given signal-to-noise ratio in dB, and the same seed gives the same noise.
+/// Returns false on error, with the message available through `last_error_ptr`.
+#[no_mangle]
+pub fn set_example_noise(kind: u32, snr_db: f32, seed: u32) -> bool {
+    let kind = match kind {
+        0 => None,
+        1 => Some(noise::NoiseKind::White),
+        2 => Some(noise::NoiseKind::Uniform),
+        3 => Some(noise::NoiseKind::Pink),
+        4 => Some(noise::NoiseKind::Brown),
+        _ => { report_error(&format!("Unknown noise code: {}", kind)); return false; }
+    };
+    if !snr_db.is_finite() {
+        report_error(&format!("Invalid SNR: {} dB", snr_db));
+        return false;
+    }
+
+    EXAMPLE_NOISE.with(|cell| { *cell.borrow_mut() = kind.map(|kind| ExampleNoise { kind, snr_db, seed: seed as u64 }); });
+    // The noise is part of the cached example, so force its regeneration (user signals are kept)
+    EXAMPLE_CACHE.with(|cell| {
+        let mut cache = cell.borrow_mut();
+        if cache.as_ref().is_some_and(|cache| cache.source != SignalSource::User) { *cache = None; }
+    });
+    true
+}
+
+/// Returns a quality metric of the example signal (or of its filtered version of the last plot),
+/// or NaN if it could not be measured or no signal is loaded.
+/// metric: 0=SNR (dB), 1=THD (dB), 2=SINAD (dB), 3=ENOB (bits)
+#[no_mangle]
+pub fn example_quality(metric: u32, filtered: bool) -> f32 {
+    EXAMPLE_CACHE.with(|cell| {
+        let quality = cell.borrow().as_ref().and_then(|cache| cache.quality[filtered as usize]);
+        quality.map_or(f32::NAN, |quality| match metric {
+            0 => quality.snr,
+            1 => quality.thd,
+            2 => quality.sinad,
+            3 => quality.enob,
+            _ => f32::NAN,
+        })
+    })
+}
+
+/// Returns the period in seconds of the example signal estimated from its autocorrelation,
+/// or NaN if it is not periodic or no signal is loaded.
+#[no_mangle]
+pub fn example_period() -> f32 {
+    EXAMPLE_CACHE.with(|cell| cell.borrow().as_ref().and_then(|cache| cache.period).unwrap_or(f32::NAN))
+}
+
+/// Select the basis of the example frequency range; it takes effect on the next `plot_example`.
+/// kind: 0=DFT (periodic extension), 1=DCT-II (even extension). The DCT keeps the same band in Hz,
+/// i.e. coefficients [2 k_min, 2 k_max], and mask tapers are measured in DCT coefficients.
+/// Returns false on error, with the message available through `last_error_ptr`.
+#[no_mangle]
+pub fn set_example_basis(kind: u32) -> bool {
+    let basis = match kind {
+        0 => ExampleBasis::Fourier,
+        1 => ExampleBasis::Cosine,
+        _ => { report_error(&format!("Unknown basis code: {}", kind)); return false; }
+    };
+    EXAMPLE_BASIS.with(|cell| { *cell.borrow_mut() = basis; });
+    true
+}
+
+/// Select the filter overlaid on the example signal; it takes effect on the next `plot_example`.
+/// design: 0=bin range (Fourier), 1=windowed-sinc FIR (Hamming, order+1 taps), 2=Butterworth,
+///         3=Chebyshev I (1 dB ripple), 4=Chebyshev II (40 dB attenuation), 5=Bessel
+/// response: 0=low-pass, 1=high-pass, 2=band-pass, 3=band-stop
+/// Cutoffs are in Hz; `cutoff_high` is only used by band-pass and band-stop. With `zero_phase`
+/// the filter is applied forwards and backwards (filtfilt) instead of causally.
+/// Returns false on error, with the message available through `last_error_ptr`.
+#[no_mangle]
+pub fn set_example_filter(design: u32, response: u32, order: usize, cutoff: f32, cutoff_high: f32, zero_phase: bool) -> bool {
+    let response = match response {
+        0 => filter::Response::LowPass(cutoff),
+        1 => filter::Response::HighPass(cutoff),
+        2 => filter::Response::BandPass(cutoff, cutoff_high),
+        3 => filter::Response::BandStop(cutoff, cutoff_high),
+        _ => { report_error(&format!("Unknown filter response code: {}", response)); return false; }
+    };
+    let design = match design {
+        0 => { EXAMPLE_FILTER.with(|cell| { *cell.borrow_mut() = None; }); return true; },
+        1 => ExampleDesign::Fir,
+        2 => ExampleDesign::Iir(filter::IirDesign::Butterworth),
+        3 => ExampleDesign::Iir(filter::IirDesign::ChebyshevI(1.0)),
+        4 => ExampleDesign::Iir(filter::IirDesign::ChebyshevII(40.0)),
+        5 => ExampleDesign::Iir(filter::IirDesign::Bessel),
+        _ => { report_error(&format!("Unknown filter design code: {}", design)); return false; }
+    };
+
+    // Check the design against the sample rate of the current signal
+    let example_filter = ExampleFilter { design, response, order, zero_phase };
+    let sample_rate = EXAMPLE_CACHE.with(|cell| cell.borrow().as_ref().and_then(|cache| cache.fourier.sample_rate()));
+    match example_filter.build(sample_rate.unwrap_or(EXAMPLE_SAMPLE_RATE)) {
+        Ok(_) => { EXAMPLE_FILTER.with(|cell| { *cell.borrow_mut() = Some(example_filter); }); true },
+        Err(msg) => { report_error(&format!("Error designing filter: {}", msg)); false },
+    }
 }
 
 ///////////////////////////////////////////////////////////////////////////////
@@ -223,6 +1070,7 @@ fn init_animation_on_canvas(k_min: usize, k_max: usize, example_code: usize) {
                 // Set the x-axis range to focus on significant frequencies
                 spectrum_plt.set_x_range(min_freq, max_freq);
                 spectrum_plt.show();
+                plot_phase_panel("animation-phase-canvas", &fourier, 1.0, (min_freq, max_freq));
             }
 
             // Create and start the animation using the same Fourier transforms
@@ -263,4 +1111,105 @@ pub fn increase_animation_speed() {
 #[no_mangle]
 pub fn decrease_animation_speed() {
     animation::decrease_animation_speed();
-}
\ No newline at end of file
+}
+///////////////////////////////////////////////////////////////////////////////
+// Image filtering (2D Fourier transform)
+///////////////////////////////////////////////////////////////////////////////
+
+/// Largest number of cells drawn along each side of an image; larger images are averaged down.
+const MAX_IMAGE_CELLS: usize = 128;
+
+/// Draw a width x height grid of values as a grayscale image stretched over the whole canvas.
+/// Each cell is a single rectangle, so grids larger than `MAX_IMAGE_CELLS` per side are box-averaged first.
+fn draw_grayscale(canvas_name: &str, width: usize, height: usize, values: &[f32]) {
+    let (columns, rows) = (width.min(MAX_IMAGE_CELLS), height.min(MAX_IMAGE_CELLS));
+    let mut cells = Vec::with_capacity(columns * rows);
+    for row in 0..rows {
+        let (y0, y1) = (row * height / rows, (row + 1) * height / rows);
+        for column in 0..columns {
+            let (x0, x1) = (column * width / columns, (column + 1) * width / columns);
+            let sum: f32 = (y0..y1).map(|y| values[y * width + x0..y * width + x1].iter().sum::<f32>()).sum();
+            cells.push(sum / ((x1 - x0) * (y1 - y0)) as f32);
+        }
+    }
+
+    let canvas = canvas::Canvas::from_element(canvas_name);
+    let (min, max) = cells.iter().fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+    let range = if max > min { max - min } else { 1.0 };
+
+    let cell_width = canvas.width() / columns as f32;
+    let cell_height = canvas.height() / rows as f32;
+    canvas.clear();
+    for (i, &value) in cells.iter().enumerate() {
+        let (x, y) = ((i % columns) as f32, (i / columns) as f32);
+        let level = (255.0 * (value - min) / range) as u8;
+        // Slightly oversized cells avoid seams between neighbouring pixels
+        canvas.fill_rect(x * cell_width, y * cell_height, cell_width + 0.5, cell_height + 0.5, 0.0, (level, level, level));
+    }
+}
+
+/// Transform an image (channels: 1=grayscale, 4=RGBA) and plot its log-magnitude spectrum.
+/// Returns false on error, with the message available through `last_error_ptr`.
+///
+/// # Safety
+/// `ptr` must point to `width * height * channels` initialized bytes, e.g. a buffer from `alloc_u8_buffer`.
+/// It is not read if the dimensions or the number of channels are invalid.
+#[no_mangle]
+pub unsafe fn load_image(ptr: *const u8, width: usize, height: usize, channels: u32) -> bool {
+    let len = if channels != 1 && channels != 4 {
+        Err(format!("Unsupported number of channels: {}", channels))
+    } else if width == 0 || height == 0 {
+        Err(format!("The image is empty ({}x{} pixels)", width, height))
+    } else {
+        width.checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(channels as usize))
+            .ok_or_else(|| format!("The image is too large ({}x{} pixels)", width, height))
+    };
+    let result = len
+        .and_then(|len| {
+            let pixels = std::slice::from_raw_parts(ptr, len);
+            if channels == 1 {
+                fourier2d::Fourier2D::from_grayscale(width, height, pixels)
+            } else {
+                fourier2d::Fourier2D::from_rgba(width, height, pixels)
+            }
+        })
+        .map_err(|msg| format!("Invalid image: {}", msg));
+
+    match result {
+        Ok(fourier) => {
+            draw_grayscale("image-spectrum-canvas", width, height, &fourier.log_magnitude_spectrum(true));
+            draw_grayscale("image-filtered-canvas", width, height, fourier.original());
+            IMAGE_CACHE.with(|cell| { *cell.borrow_mut() = Some(fourier); });
+            true
+        },
+        Err(msg) => { report_error(&msg); false },
+    }
+}
+
+/// Filter the loaded image with a circular mask and plot the result and the masked spectrum.
+/// kind: 0=low-pass(radius), 1=high-pass(radius), 2=band-pass(radius, outer_radius)
+/// Returns false on error, with the message available through `last_error_ptr`.
+#[no_mangle]
+pub fn filter_image(kind: u32, radius: f32, outer_radius: f32) -> bool {
+    let mask = match kind {
+        0 => fourier2d::Mask2D::Low(radius),
+        1 => fourier2d::Mask2D::High(radius),
+        2 => fourier2d::Mask2D::Band(radius, outer_radius),
+        _ => { report_error(&format!("Unknown mask code: {}", kind)); return false; }
+    };
+
+    let result = IMAGE_CACHE.with(|cell| {
+        let cache = cell.borrow();
+        let fourier = cache.as_ref().ok_or("No image loaded")?;
+        let (width, height) = (fourier.width(), fourier.height());
+        let spectrum = fourier.masked_log_magnitude_spectrum(mask)
+            .map_err(|msg| format!("Error masking spectrum: {}", msg))?;
+        let image = fourier.filtered(mask)
+            .map_err(|msg| format!("Error filtering image: {}", msg))?;
+        draw_grayscale("image-spectrum-canvas", width, height, &spectrum);
+        draw_grayscale("image-filtered-canvas", width, height, &image);
+        Ok::<_, String>(())
+    });
+    result.map_err(|msg| report_error(&msg)).is_ok()
+}
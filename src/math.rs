@@ -5,24 +5,104 @@
 /// Note: For a DFT of length N, the frequency index N-k is equivalent to -k,
 /// so negative frequencies are represented by the upper half of the spectrum.
 /// For real input, DFT coefficients at k and N-k are complex conjugates (Hermitian symmetry).
-pub use num_complex::Complex32;
+///
+/// All transforms are generic over the floating-point precision (`f32` or `f64`, see `Real`).
+/// Twiddle factors and chirps are always evaluated in double precision before conversion.
+pub use num_complex::{Complex, Complex32};
+use num_traits::{Float, FloatConst, NumAssign, Zero};
+
+/// Floating-point type usable by the transforms, implemented for `f32` and `f64`.
+pub trait Real: Float + FloatConst + NumAssign + std::iter::Sum + std::fmt::Debug + std::fmt::Display + 'static {}
+
+impl Real for f32 {}
+impl Real for f64 {}
+
+/// Largest prime factor handled by the mixed-radix FFT before switching to Bluestein's algorithm.
+const BLUESTEIN_THRESHOLD: usize = 64;
 
-pub struct Fourier {
-    original: Vec<Complex32>,
-    // DFT coefficients for all frequencies (0..N-1).
-    transform: Vec<Complex32>,
+pub struct Fourier<T: Real = f32> {
+    original: Vec<Complex<T>>,
+    // Length of the transform, larger than the original signal when zero-padded.
+    total_points: usize,
+    // DFT coefficients for all frequencies (0..N-1) for complex input. For real input only the
+    // non-redundant half (0..=N/2) is stored, since X[N-k] = conj(X[k]).
+    transform: Vec<Complex<T>>,
+    real_input: bool,
+    // Window applied before the transform and its coherent gain (mean of the coefficients).
+    window: Window,
+    window_gain: T,
+    // Samples per second, if known. Frequencies are expressed in bins otherwise.
+    sample_rate: Option<T>,
 }
 
-impl Fourier {
+impl<T: Real> Fourier<T> {
     /// Constructs a Fourier object from a vector of real values.
     /// Returns an error if the input is empty or contains invalid values.
-    pub fn from_real(data: Vec<f32>) -> Result<Self, String> {
-        Self::from_complex(data.iter().map(|&x| Complex32::new(x, 0.0)).collect())
+    pub fn from_real(data: Vec<T>) -> Result<Self, String> {
+        Self::from_real_windowed_padded(data, Window::Rectangular, Padding::None)
+    }
+
+    /// Constructs a Fourier object from real values multiplied by the given window.
+    ///
+    /// `original()` still returns the unwindowed signal, while the transform (and hence `filtered_range`)
+    /// refers to the windowed one. `power_spectrum` divides by the squared coherent gain of the window,
+    /// so the peak power of a sinusoid is comparable to the unwindowed spectrum.
+    /// Returns an error if the input or the window parameters are invalid.
+    pub fn from_real_windowed(data: Vec<T>, window: Window) -> Result<Self, String> {
+        Self::from_real_windowed_padded(data, window, Padding::None)
+    }
+
+    /// Constructs a Fourier object from real values zero-padded as requested.
+    ///
+    /// Padding interpolates the spectrum on a finer frequency grid: frequency indices then refer to the
+    /// padded transform, while `original()` and `filtered_range` cover only the original samples.
+    /// Returns an error if the input is invalid or the padded length is shorter than the signal.
+    pub fn from_real_padded(data: Vec<T>, padding: Padding) -> Result<Self, String> {
+        Self::from_real_windowed_padded(data, Window::Rectangular, padding)
+    }
+
+    /// Constructs a Fourier object from real values, applying the window and then zero-padding.
+    /// See `from_real_windowed` and `from_real_padded` for details.
+    pub fn from_real_windowed_padded(data: Vec<T>, window: Window, padding: Padding) -> Result<Self, String> {
+        // Validate input
+        if data.is_empty() { return Err("Input vector is empty".to_string()); }
+        // Check for NaN or Inf values
+        if data.iter().any(|val| !val.is_finite()) {
+            return Err("Input vector contains invalid values (NaN or Inf)".to_string());
+        }
+        window.validate()?;
+        let total_points = padding.padded_length(data.len())?;
+
+        let coefficients = window.coefficients(data.len());
+        let mut windowed: Vec<T> = data.iter().zip(coefficients.iter()).map(|(&x, &w)| x * w).collect();
+        windowed.resize(total_points, T::zero());
+
+        Ok(Fourier {
+            transform: rfft(&windowed),
+            original: data.iter().map(|&x| Complex::new(x, T::zero())).collect(),
+            total_points,
+            real_input: true,
+            window,
+            window_gain: cast(window.coherent_gain(data.len())),
+            sample_rate: None,
+        })
     }
 
     /// Constructs a Fourier object from a vector of complex values.
     /// Returns an error if the input is empty or contains invalid values.
-    pub fn from_complex(data: Vec<Complex32>) -> Result<Self, String> {
+    pub fn from_complex(data: Vec<Complex<T>>) -> Result<Self, String> {
+        Self::from_complex_padded(data, Padding::None)
+    }
+
+    /// Constructs a Fourier object from complex values zero-padded as requested.
+    /// See `from_real_padded` for details.
+    pub fn from_complex_padded(data: Vec<Complex<T>>, padding: Padding) -> Result<Self, String> {
+        Self::from_complex_windowed_padded(data, Window::Rectangular, padding)
+    }
+
+    /// Constructs a Fourier object from complex values, applying the window and then zero-padding.
+    /// See `from_real_windowed` and `from_real_padded` for details.
+    pub fn from_complex_windowed_padded(data: Vec<Complex<T>>, window: Window, padding: Padding) -> Result<Self, String> {
         // Validate input
         if data.is_empty() { return Err("Input vector is empty".to_string()); }
         // Check for NaN or Inf values
@@ -31,104 +111,2458 @@ impl Fourier {
                 return Err("Input vector contains invalid values (NaN or Inf)".to_string());
             }
         }
+        window.validate()?;
+        let total_points = padding.padded_length(data.len())?;
+
+        let coefficients: Vec<T> = window.coefficients(data.len());
+        let mut windowed: Vec<Complex<T>> = data.iter().zip(coefficients.iter()).map(|(&x, &w)| x * w).collect();
+        windowed.resize(total_points, Complex::zero());
 
         Ok(Fourier {
-            transform: dft(&data),
+            transform: fft(&windowed, false),
+            window_gain: cast(window.coherent_gain(data.len())),
             original: data,
+            total_points,
+            real_input: false,
+            window,
+            sample_rate: None,
         })
     }
 
+    /// Attaches the sample rate (in Hz) of the signal, enabling physical frequency units.
+    /// Returns an error if the sample rate is not a positive finite number.
+    pub fn with_sample_rate(mut self, sample_rate: T) -> Result<Self, String> {
+        if !sample_rate.is_finite() || sample_rate <= T::zero() {
+            return Err(format!("Sample rate must be a positive number, got {}", sample_rate));
+        }
+        self.sample_rate = Some(sample_rate);
+        Ok(self)
+    }
+
+    /// Attaches the time between consecutive samples (in seconds). See `with_sample_rate`.
+    pub fn with_sample_spacing(self, spacing: T) -> Result<Self, String> {
+        if !spacing.is_finite() || spacing <= T::zero() {
+            return Err(format!("Sample spacing must be a positive number, got {}", spacing));
+        }
+        self.with_sample_rate(T::one() / spacing)
+    }
+
     /// Reconstructs the signal using only the frequency range [k_min, k_max].
     ///
     /// k_min and k_max are absolute frequency indices (0-based).
     /// Due to the DFT frequency symmetry (N-k ~ -k), this includes both positive and negative frequencies
     /// by summing the corresponding coefficients. For real input, coefficients at k and N-k are complex conjugates.
     /// Returns an error if the range is invalid.
-    pub fn filtered_range(&self, k_min: usize, k_max: usize) -> Result<Vec<Complex32>, String> {
+    pub fn filtered_range(&self, k_min: usize, k_max: usize) -> Result<Vec<Complex<T>>, String> {
         let max_k = self.max_frequency();
         if k_min > k_max || k_max > max_k {
             return Err(format!("Frequency range [{}, {}] out of bounds (max {})", k_min, k_max, max_k));
         }
-        Ok(idft(&self.transform, k_min, k_max))
+        self.filtered_with_mask(&SpectralMask::Range(k_min, k_max))
+    }
+
+    /// Reconstructs the signal after multiplying each DFT coefficient by the gain of the mask.
+    /// See `filtered_with_gain` for how frequencies are measured.
+    /// Returns an error if the mask parameters are invalid.
+    pub fn filtered_with_mask(&self, mask: &SpectralMask) -> Result<Vec<Complex<T>>, String> {
+        mask.validate()?;
+        Ok(self.filtered_with_gain(|k| cast(mask.gain(to_f64(k)))))
+    }
+
+    /// Reconstructs the signal after multiplying each DFT coefficient by `gain(|k|)`.
+    ///
+    /// The gain is a function of the absolute frequency index (0..=N/2 in bins of the transform), so
+    /// positive and negative frequencies are scaled alike and real input stays real.
+    pub fn filtered_with_gain<F: Fn(T) -> T>(&self, gain: F) -> Vec<Complex<T>> {
+        let n = self.total_points;
+        if self.real_input {
+            // Negative frequencies are implied by the Hermitian symmetry of the half spectrum
+            let masked: Vec<Complex<T>> = self.transform.iter().enumerate()
+                .map(|(k, &c)| c * gain(cast(k as f64)))
+                .collect();
+            let mut result = irfft(&masked, n);
+            result.truncate(self.size());
+            return result.into_iter().map(|x| Complex::new(x, T::zero())).collect();
+        }
+        // Bin k holds frequency k for k <= N/2 and frequency k - N (negative) above it
+        let masked: Vec<Complex<T>> = self.transform.iter().enumerate()
+            .map(|(k, &c)| c * gain(cast(k.min(n - k) as f64)))
+            .collect();
+        let mut result = fft(&masked, true);
+        result.truncate(self.size());
+        result
+    }
+
+    /// Reconstructs the signal using only the frequencies between f_min and f_max (in Hz, inclusive).
+    ///
+    /// The cutoffs are converted to the bins of the transform that lie inside the band, then the
+    /// reconstruction proceeds as in `filtered_range`. A band containing no bin yields a zero signal.
+    /// Returns an error if no sample rate is set or the band is not within [0, sample_rate / 2].
+    pub fn filtered_hz(&self, f_min: T, f_max: T) -> Result<Vec<Complex<T>>, String> {
+        let sample_rate = self.require_sample_rate()?;
+        let nyquist = sample_rate / cast(2.0);
+        if !(f_min >= T::zero() && f_min <= f_max && f_max <= nyquist) {
+            return Err(format!("Frequency band [{}, {}] Hz out of bounds (Nyquist {} Hz)", f_min, f_max, nyquist));
+        }
+
+        let bins_per_hz = cast::<T>(self.total_points as f64) / sample_rate;
+        let k_min = to_f64((f_min * bins_per_hz).ceil()) as usize;
+        let k_max = (to_f64((f_max * bins_per_hz).floor()) as usize).min(self.max_frequency());
+        if k_min > k_max {
+            return Ok(vec![Complex::zero(); self.size()]);
+        }
+        self.filtered_range(k_min, k_max)
+    }
+
+    /// Resamples the original signal to `new_len` samples covering the same interval, by zero-padding or
+    /// truncating its spectrum (band-limited interpolation, treating the signal as periodic).
+    ///
+    /// When shortening, frequencies above the new Nyquist frequency are discarded. For even lengths the
+    /// Nyquist bin is shared between the positive and negative frequencies, so real input stays real.
+    /// Window and padding are ignored: the result always refers to the original samples.
+    /// Returns an error if `new_len` is zero.
+    pub fn resample(&self, new_len: usize) -> Result<Vec<Complex<T>>, String> {
+        if new_len == 0 { return Err("Resampled length must be positive".to_string()); }
+        let n = self.size();
+        let m = new_len;
+        let spectrum: Vec<Complex<T>> = if self.window == Window::Rectangular && self.total_points == n {
+            (0..n).map(|k| self.coefficient(k)).collect()
+        } else {
+            fft(&self.original, false)
+        };
+
+        // Bins 0..positive are the DC and positive frequencies, the last `negative` bins the negative ones
+        let shared = n.min(m);
+        let positive = shared / 2 + 1;
+        let negative = shared - positive;
+        let mut resampled = vec![Complex::zero(); m];
+        resampled[..positive].copy_from_slice(&spectrum[..positive]);
+        resampled[m - negative..].copy_from_slice(&spectrum[n - negative..]);
+        if shared.is_multiple_of(2) {
+            if m < n {
+                // Both +m/2 and -m/2 of the original fold onto the new Nyquist bin
+                resampled[m / 2] = spectrum[m / 2] + spectrum[n - m / 2];
+            } else if m > n {
+                // Split the old Nyquist bin evenly between +n/2 and -n/2
+                let half = spectrum[n / 2] * cast::<T>(0.5);
+                resampled[n / 2] = half;
+                resampled[m - n / 2] = half;
+            }
+        }
+
+        // Unitary scaling: keep the amplitudes of the samples, not the energy
+        let scale: T = cast((m as f64 / n as f64).sqrt());
+        let mut result = fft(&resampled, true);
+        for value in result.iter_mut() {
+            *value *= scale;
+            if self.real_input { value.im = T::zero(); }
+        }
+        Ok(result)
     }
 
-    /// Returns the power spectrum (magnitude squared) and phase (angle) of the DFT coefficients as two vectors.
+    /// Returns the frequencies and the power spectrum (magnitude squared) of the DFT coefficients as two vectors.
     /// If `shifted` is true, the output is fftshifted (zero frequency centered).
-    pub fn power_spectrum(&self, shifted: bool) -> (Vec<f32>, Vec<f32>) {
-        let n = self.transform.len();
-        let mut freq: Vec<f32> = (0..n).map(|k| k as f32).collect();
-        let mut powers: Vec<f32> = self.transform.iter().map(|c| c.norm_sqr()).collect();
+    /// Powers are corrected by the coherent gain of the window and of the zero-padding, if any.
+    pub fn power_spectrum(&self, shifted: bool) -> (Vec<T>, Vec<T>) {
+        let n = self.total_points;
+        let correction = self.power_correction();
+        let mut freq: Vec<T> = (0..n).map(|k| cast(k as f64)).collect();
+        let mut powers: Vec<T> = (0..n).map(|k| self.coefficient(k).norm_sqr() * correction).collect();
         if shifted {
-            let max_k = self.max_frequency()+1;
-            freq.rotate_left(max_k);
-            powers.rotate_left(max_k);
-            for i in 0..max_k {
+            // Bins from ceil(N/2) on (the Nyquist bin included for even N) are negative frequencies
+            let first_negative = n.div_ceil(2);
+            freq.rotate_left(first_negative);
+            powers.rotate_left(first_negative);
+            for f in freq.iter_mut().take(n - first_negative) {
                 // Adjust frequencies to be centered around zero
-                freq[i] -= n as f32;
+                *f -= cast(n as f64);
             }
         }
         (freq, powers)
     }
 
+    /// Returns the frequencies and the phase (angle, in radians) of the DFT coefficients, ordered like `power_spectrum`.
+    /// Phases are wrapped to (-pi, pi], or unwrapped along the frequency order when `unwrapped` is true.
+    /// The phase of bins where the transform vanishes is only rounding noise, so it is set to zero.
+    /// Bins with small but significant power can still disturb the unwrapping; use `unwrap_phase` on a
+    /// selection of bins in that case.
+    pub fn phase_spectrum(&self, shifted: bool, unwrapped: bool) -> (Vec<T>, Vec<T>) {
+        let (freq, _) = self.power_spectrum(shifted);
+        let threshold = self.negligible_magnitude();
+        let n = self.total_points as isize;
+        let phases: Vec<T> = freq.iter()
+            .map(|&f| {
+                let coefficient = self.coefficient((to_f64(f) as isize).rem_euclid(n) as usize);
+                if coefficient.norm() <= threshold { T::zero() } else { coefficient.arg() }
+            })
+            .collect();
+        if unwrapped { (freq, unwrap_phase(&phases)) } else { (freq, phases) }
+    }
+
+    /// Returns the frequencies and the group delay (-d phase / d omega, in samples), ordered like `power_spectrum`.
+    ///
+    /// The delay is computed exactly as Re(DFT(n x[n]) / DFT(x[n])) on the (windowed, padded) signal rather than
+    /// by differentiating the phase. It is set to zero at bins where the transform vanishes.
+    pub fn group_delay(&self, shifted: bool) -> (Vec<T>, Vec<T>) {
+        let (freq, _) = self.power_spectrum(shifted);
+        let coefficients = self.window.coefficients::<T>(self.size());
+        let mut ramp: Vec<Complex<T>> = self.original.iter().zip(coefficients.iter()).enumerate()
+            .map(|(i, (&x, &w))| x * w * cast::<T>(i as f64))
+            .collect();
+        ramp.resize(self.total_points, Complex::zero());
+        let ramp_transform = fft(&ramp, false);
+
+        let threshold = self.negligible_magnitude();
+        let n = self.total_points as isize;
+        let delays = freq.iter()
+            .map(|&f| {
+                let k = (to_f64(f) as isize).rem_euclid(n) as usize;
+                let coefficient = self.coefficient(k);
+                if coefficient.norm() <= threshold { T::zero() } else { (ramp_transform[k] / coefficient).re }
+            })
+            .collect();
+        (freq, delays)
+    }
+
+    /// Returns the analytic signal: the inverse transform of the spectrum with negative frequencies removed and
+    /// positive ones doubled, so that for real input its real part is the signal and its imaginary part
+    /// the Hilbert transform. DC and (for even lengths) Nyquist bins are kept as they are.
+    /// The result covers the original samples and refers to the windowed signal if a window was applied.
+    pub fn analytic_signal(&self) -> Vec<Complex<T>> {
+        let n = self.total_points;
+        let two: T = cast(2.0);
+        let one_sided: Vec<Complex<T>> = (0..n)
+            .map(|k| {
+                if k == 0 || 2 * k == n { self.coefficient(k) }
+                else if 2 * k < n { self.coefficient(k) * two }
+                else { Complex::zero() }
+            })
+            .collect();
+        let mut result = fft(&one_sided, true);
+        result.truncate(self.size());
+        result
+    }
+
+    /// Returns the power spectrum like `power_spectrum`, with frequencies expressed in the given unit.
+    /// Returns an error if a physical unit is requested and no sample rate is set.
+    pub fn power_spectrum_in(&self, shifted: bool, unit: FrequencyUnit) -> Result<(Vec<T>, Vec<T>), String> {
+        let (mut freq, powers) = self.power_spectrum(shifted);
+        let scale = self.bin_scale(unit)?;
+        freq.iter_mut().for_each(|f| *f *= scale);
+        Ok((freq, powers))
+    }
+
+    /// Returns a high-resolution power spectrum over the sub-band [k_start, k_end] using the zoom FFT.
+    /// Frequencies are fractional bin indices (negative values are negative frequencies), and powers
+    /// use the same scaling as `power_spectrum`, so both agree at integer frequencies.
+    pub fn zoom_spectrum(&self, k_start: T, k_end: T, points: usize) -> Result<(Vec<T>, Vec<T>), String> {
+        let coefficients = self.window.coefficients::<T>(self.size());
+        let mut windowed: Vec<Complex<T>> = self.original.iter().zip(coefficients.iter()).map(|(&x, &w)| x * w).collect();
+        windowed.resize(self.total_points, Complex::zero());
+
+        let values = zoom_fft(&windowed, k_start, k_end, points)?;
+        let step = (k_end - k_start) / cast((points - 1) as f64);
+        let correction = self.power_correction();
+        let freq = (0..points).map(|i| k_start + step * cast(i as f64)).collect();
+        let powers = values.iter().map(|c| c.norm_sqr() * correction).collect();
+        Ok((freq, powers))
+    }
+
+    /// Returns up to `max_peaks` spectral peaks, strongest first, among those with at least `min_prominence`
+    /// (in the units of `power_spectrum`).
+    ///
+    /// Peak frequencies are refined between bins with the chosen interpolation, and the power is the
+    /// spectrum evaluated at that refined frequency, so it tracks the true height of the peak rather than
+    /// that of the nearest bin. Frequencies are fractional bin indices (negative for negative frequencies);
+    /// for real input only non-negative frequencies are returned.
+    pub fn spectral_peaks(&self, max_peaks: usize, min_prominence: T, interpolation: PeakInterpolation) -> Vec<SpectralPeak<T>> {
+        let n = self.total_points as isize;
+        let (freq, power) = self.power_spectrum(true);
+
+        let mut peaks: Vec<SpectralPeak<T>> = find_peaks(&power, min_prominence)
+            .into_iter()
+            .filter(|&(i, _)| !self.real_input || freq[i] >= T::zero())
+            .map(|(i, prominence)| {
+                let k = to_f64(freq[i]) as isize;
+                let neighbour = |offset: isize| self.coefficient((k + offset).rem_euclid(n) as usize);
+                let offset = interpolation.offset(neighbour(-1), neighbour(0), neighbour(1));
+                let frequency = freq[i] + offset;
+                SpectralPeak { frequency, power: self.power_at(frequency), prominence }
+            })
+            .collect();
+
+        peaks.sort_by(|a, b| b.power.partial_cmp(&a.power).unwrap_or(std::cmp::Ordering::Equal));
+        peaks.truncate(max_peaks);
+        peaks
+    }
+
+    /// Measures the quality of a real sinusoidal signal: the largest peak (away from DC) is taken as the
+    /// fundamental, multiples of it up to `max_harmonic` as harmonics, and every other bin except DC as noise.
+    ///
+    /// Each component gathers the power of the bins within the main lobe of the window around it, so tones
+    /// between bins are only measured accurately with a window of low leakage (e.g. Blackman or flat-top).
+    /// Harmonics above the Nyquist frequency are folded back, as they would alias when sampling.
+    /// Returns an error for complex input or if no fundamental can be found.
+    pub fn signal_quality(&self, max_harmonic: usize) -> Result<SignalQuality<T>, String> {
+        if !self.real_input { return Err("Signal quality is only defined for real input".to_string()); }
+        let n = self.total_points;
+        let half = n / 2;
+        // Main lobe half width, in bins of the padded transform
+        let span = self.window.main_lobe_half_width() * n.div_ceil(self.size());
+        // One-sided power: every bin but DC and Nyquist stands for two frequencies
+        let powers: Vec<f64> = (0..=half)
+            .map(|k| {
+                let power = to_f64(self.transform[k].norm_sqr());
+                if k == 0 || 2 * k == n { power } else { 2.0 * power }
+            })
+            .collect();
+
+        let mut roles = vec![BinRole::Noise; half + 1];
+        let mark = |center: usize, role: BinRole, roles: &mut [BinRole]| {
+            for value in &mut roles[center.saturating_sub(span)..=(center + span).min(half)] {
+                if *value == BinRole::Noise { *value = role; }
+            }
+        };
+        mark(0, BinRole::Dc, &mut roles);
+        let peak = (0..=half).filter(|&k| roles[k] == BinRole::Noise)
+            .max_by(|&a, &b| powers[a].total_cmp(&powers[b]))
+            .ok_or("Signal too short to locate a fundamental away from DC")?;
+        mark(peak, BinRole::Fundamental, &mut roles);
+
+        // Power-weighted centroid of the main lobe, between bins
+        let lobe = (0..=half).filter(|&k| roles[k] == BinRole::Fundamental);
+        let lobe_power: f64 = lobe.clone().map(|k| powers[k]).sum();
+        let fundamental = lobe.map(|k| k as f64 * powers[k]).sum::<f64>() / lobe_power;
+        for harmonic in 2..=max_harmonic {
+            let position = (harmonic as f64 * fundamental).round() as usize % n;
+            mark(position.min(n - position), BinRole::Harmonic, &mut roles);
+        }
+
+        let total = |role: BinRole| (0..=half).filter(|&k| roles[k] == role).map(|k| powers[k]).sum::<f64>();
+        let (signal, harmonics, noise) = (total(BinRole::Fundamental), total(BinRole::Harmonic), total(BinRole::Noise));
+        let decibels = |ratio: f64| cast::<T>(10.0 * ratio.log10());
+        let sinad = 10.0 * (signal / (noise + harmonics)).log10();
+        Ok(SignalQuality {
+            fundamental: cast(fundamental),
+            snr: decibels(signal / noise),
+            thd: decibels(harmonics / signal),
+            sinad: cast(sinad),
+            enob: cast((sinad - 1.76) / 6.02),
+        })
+    }
+
     /// Returns the value of a single frequency component at a given time step.
-    pub fn get_component(&self, frequency: usize, time_step: usize) -> Complex32 {
-        let total_points = self.transform.len();
+    pub fn get_component(&self, frequency: usize, time_step: usize) -> Complex<T> {
+        let total_points = self.total_points;
 
-        let angle = 2.0 * std::f32::consts::PI * (time_step as f32) * (frequency as f32) / (total_points as f32);
-        let exp_term = Complex32::new(0.0, angle).exp();
+        let angle = 2.0 * std::f64::consts::PI * (time_step as f64) * (frequency as f64) / (total_points as f64);
+        let exp_term = Complex::new(T::zero(), cast(angle)).exp();
 
-        self.transform[frequency] * exp_term / (total_points as f32).sqrt()
+        self.coefficient(frequency) * exp_term / cast::<T>(total_points as f64).sqrt()
     }
 
     /// Returns the number of points in the original signal.
     pub fn size(&self) -> usize { self.original.len() }
+    /// Returns the number of points in the transform (the padded length when zero-padding is used).
+    pub fn transform_size(&self) -> usize { self.total_points }
     /// Returns a reference to the original signal data.
-    pub fn original(&self) -> &[Complex32] { &self.original }
+    pub fn original(&self) -> &[Complex<T>] { &self.original }
+    /// Returns true if the transform was built from real values (see `from_real`).
+    pub fn is_real(&self) -> bool { self.real_input }
+
+    /// Returns the window applied before the transform.
+    pub fn window(&self) -> Window { self.window }
+    /// Returns the equivalent noise bandwidth of the applied window, in bins of the unpadded transform.
+    /// Divide `power_spectrum` values by it to compare broadband noise power across windows.
+    pub fn equivalent_noise_bandwidth(&self) -> T { cast(self.window.enbw(self.size())) }
+
+    /// Returns the maximum frequency index (N/2 - 1 for N points in the transform, 0 for fewer than 4 points).
+    pub fn max_frequency(&self) -> usize { (self.total_points / 2).saturating_sub(1) }
+
+    /// Returns the sample rate in Hz, if one was attached.
+    pub fn sample_rate(&self) -> Option<T> { self.sample_rate }
+    /// Returns the frequency spacing between bins of the transform in Hz, if the sample rate is known.
+    pub fn frequency_resolution(&self) -> Option<T> {
+        self.sample_rate.map(|fs| fs / cast(self.total_points as f64))
+    }
+
+    /// Converts a (possibly fractional) frequency index into the given unit.
+    /// Returns an error if a physical unit is requested and no sample rate is set.
+    pub fn bin_to_frequency(&self, k: T, unit: FrequencyUnit) -> Result<T, String> {
+        Ok(k * self.bin_scale(unit)?)
+    }
+
+    /// Converts a frequency in the given unit into a (possibly fractional) frequency index.
+    /// Returns an error if a physical unit is requested and no sample rate is set.
+    pub fn frequency_to_bin(&self, frequency: T, unit: FrequencyUnit) -> Result<T, String> {
+        Ok(frequency / self.bin_scale(unit)?)
+    }
+
+    /// Size of one bin of the transform in the given unit.
+    fn bin_scale(&self, unit: FrequencyUnit) -> Result<T, String> {
+        match unit {
+            FrequencyUnit::Bin => Ok(T::one()),
+            FrequencyUnit::Hertz => Ok(self.require_sample_rate()? / cast(self.total_points as f64)),
+            FrequencyUnit::RadiansPerSecond => Ok(T::TAU() * self.require_sample_rate()? / cast(self.total_points as f64)),
+        }
+    }
+
+    fn require_sample_rate(&self) -> Result<T, String> {
+        self.sample_rate.ok_or_else(|| "Sample rate is not set".to_string())
+    }
+
+    /// Power of the (windowed, padded) spectrum at a fractional frequency index, with the scaling of `power_spectrum`.
+    fn power_at(&self, frequency: T) -> T {
+        let coefficients = self.window.coefficients::<f64>(self.size());
+        let step = -2.0 * std::f64::consts::PI * to_f64(frequency) / self.total_points as f64;
+        let sum = self.original.iter().zip(coefficients.iter()).enumerate()
+            .map(|(i, (x, &w))| Complex::new(to_f64(x.re), to_f64(x.im)) * w * Complex::from_polar(1.0, step * i as f64))
+            .fold(Complex::new(0.0, 0.0), |acc, x| acc + x);
+        cast::<T>(sum.norm_sqr() / self.total_points as f64) * self.power_correction()
+    }
+
+    /// Power scaling undoing the coherent gain of the window and of the zero-padding (N/M).
+    fn power_correction(&self) -> T {
+        let padding_gain: T = cast(self.total_points as f64 / self.size() as f64);
+        padding_gain / (self.window_gain * self.window_gain)
+    }
+
+    /// Returns the magnitude at or below which a coefficient is treated as zero: a small fraction
+    /// of the largest one, so that only rounding noise falls below it.
+    fn negligible_magnitude(&self) -> T {
+        let largest = (0..self.total_points).map(|k| self.coefficient(k).norm()).fold(T::zero(), |acc, x| acc.max(x));
+        largest * cast(1e-10)
+    }
 
-    /// Returns the maximum frequency index (N/2 - 1 for N points).
-    pub fn max_frequency(&self) -> usize { self.transform.len() / 2  - 1 }
+    /// Returns the DFT coefficient at frequency index k (0..N-1), rebuilding the
+    /// negative-frequency half from the Hermitian symmetry for real input.
+    fn coefficient(&self, k: usize) -> Complex<T> {
+        if self.real_input && k >= self.transform.len() {
+            self.transform[self.total_points - k].conj()
+        } else {
+            self.transform[k]
+        }
+    }
 }
 
-/// Computes the Discrete Fourier Transform (DFT) of the input data.
-fn dft(data: &[Complex32]) -> Vec<Complex32> {
-    let total_points = data.len();
-    let norm = 1.0 / (total_points as f32).sqrt();
-    let omega = Complex32::new(0.0, -2.0 * std::f32::consts::PI / total_points as f32);
+/// A local maximum of the power spectrum found by `Fourier::spectral_peaks`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpectralPeak<T: Real = f32> {
+    /// Interpolated frequency, as a fractional bin index.
+    pub frequency: T,
+    /// Power at the interpolated frequency.
+    pub power: T,
+    /// Height of the peak above the higher of its two surrounding minima.
+    pub prominence: T,
+}
+
+/// Quality metrics of a sinusoidal signal measured by `Fourier::signal_quality`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SignalQuality<T: Real = f32> {
+    /// Frequency of the fundamental, as a fractional bin index.
+    pub fundamental: T,
+    /// Signal-to-noise ratio in dB: fundamental power over the power of everything but DC and the harmonics.
+    pub snr: T,
+    /// Total harmonic distortion in dB: power of the harmonics over the fundamental power.
+    pub thd: T,
+    /// Signal-to-noise-and-distortion ratio in dB: fundamental power over noise plus harmonics.
+    pub sinad: T,
+    /// Effective number of bits of an ideal quantizer with the same SINAD: (SINAD - 1.76) / 6.02.
+    pub enob: T,
+}
+
+/// Component a bin is assigned to by `Fourier::signal_quality`.
+#[derive(Clone, Copy, PartialEq)]
+enum BinRole {
+    Noise,
+    Dc,
+    Fundamental,
+    Harmonic,
+}
+
+/// Method used to refine a spectral peak between bins from the peak bin and its two neighbours.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PeakInterpolation {
+    /// Keep the frequency of the peak bin.
+    None,
+    /// Parabola through the magnitudes.
+    Parabolic,
+    /// Parabola through the log-magnitudes, exact for Gaussian-shaped peaks (e.g. Gaussian-like windows).
+    Gaussian,
+    /// Quinn's second estimator on the complex coefficients, suited to unwindowed sinusoids.
+    Quinn,
+}
+
+impl PeakInterpolation {
+    /// Offset of the peak from the center bin, in bins, given the coefficients at k-1, k and k+1.
+    fn offset<T: Real>(&self, previous: Complex<T>, center: Complex<T>, next: Complex<T>) -> T {
+        let (previous, center, next) = (
+            Complex::new(to_f64(previous.re), to_f64(previous.im)),
+            Complex::new(to_f64(center.re), to_f64(center.im)),
+            Complex::new(to_f64(next.re), to_f64(next.im)),
+        );
+        let vertex = |a: f64, b: f64, c: f64| {
+            let denominator = a - 2.0 * b + c;
+            if denominator.abs() < f64::EPSILON { 0.0 } else { 0.5 * (a - c) / denominator }
+        };
+        let offset = match *self {
+            PeakInterpolation::None => 0.0,
+            PeakInterpolation::Parabolic => vertex(previous.norm(), center.norm(), next.norm()),
+            PeakInterpolation::Gaussian => {
+                // A zero neighbour has no logarithm; fall back on the bin itself
+                if previous.norm() > 0.0 && center.norm() > 0.0 && next.norm() > 0.0 {
+                    vertex(previous.norm().ln(), center.norm().ln(), next.norm().ln())
+                } else { 0.0 }
+            },
+            PeakInterpolation::Quinn => {
+                if center.norm() == 0.0 { 0.0 } else {
+                    let tau = |x: f64| {
+                        let root = (2.0f64 / 3.0).sqrt();
+                        0.25 * (3.0 * x * x + 6.0 * x + 1.0).ln()
+                            - 6.0f64.sqrt() / 24.0 * ((x + 1.0 - root) / (x + 1.0 + root)).ln()
+                    };
+                    let alpha_previous = (previous / center).re;
+                    let alpha_next = (next / center).re;
+                    let delta_previous = alpha_previous / (1.0 - alpha_previous);
+                    let delta_next = -alpha_next / (1.0 - alpha_next);
+                    let offset = 0.5 * (delta_previous + delta_next) + tau(delta_next * delta_next) - tau(delta_previous * delta_previous);
+                    if offset.is_finite() { offset } else { 0.0 }
+                }
+            },
+        };
+        // The refined peak should stay between its neighbours
+        cast(offset.clamp(-0.5, 0.5))
+    }
+}
+
+/// Computes the analytic signal x + i H(x) of real data through its transform (see `Fourier::analytic_signal`).
+/// Returns an error if the input is empty or contains invalid values.
+pub fn hilbert<T: Real>(data: &[T]) -> Result<Vec<Complex<T>>, String> {
+    Ok(Fourier::from_real(data.to_vec())?.analytic_signal())
+}
 
-    let mut result = Vec::<Complex32>::with_capacity(total_points);
-    for k in 0..total_points {
-        let mut res = Complex32::new(0.0, 0.0);
-        for (i, &val) in data.iter().enumerate() {
-            let angle = omega * (k as f32) * (i as f32);
-            res += val * angle.exp();
+/// Returns the envelope (instantaneous amplitude) of an analytic signal.
+pub fn envelope<T: Real>(analytic: &[Complex<T>]) -> Vec<T> {
+    analytic.iter().map(|z| z.norm()).collect()
+}
+
+/// Returns the unwrapped instantaneous phase (in radians) of an analytic signal.
+pub fn instantaneous_phase<T: Real>(analytic: &[Complex<T>]) -> Vec<T> {
+    let phases: Vec<T> = analytic.iter().map(|z| z.arg()).collect();
+    unwrap_phase(&phases)
+}
+
+/// Returns the instantaneous frequency (in Hz) of an analytic signal sampled at `sample_rate`.
+/// Value i is the phase advance from sample i to sample i + 1, so the result has one value less than the input.
+pub fn instantaneous_frequency<T: Real>(analytic: &[Complex<T>], sample_rate: T) -> Vec<T> {
+    analytic.windows(2)
+        .map(|pair| (pair[1] * pair[0].conj()).arg() / T::TAU() * sample_rate)
+        .collect()
+}
+
+/// Unwraps a sequence of phases (in radians) by adding multiples of 2 pi wherever consecutive
+/// values jump by more than pi.
+pub fn unwrap_phase<T: Real>(phases: &[T]) -> Vec<T> {
+    let mut unwrapped = Vec::with_capacity(phases.len());
+    let mut offset = T::zero();
+    for (i, &phase) in phases.iter().enumerate() {
+        if i > 0 {
+            let jump = phase - phases[i - 1];
+            // Round the jump to the nearest multiple of 2 pi and compensate it
+            offset -= (jump / T::TAU()).round() * T::TAU();
         }
-        result.push(res * norm); // Unitary scaling
+        unwrapped.push(phase + offset);
     }
-    result
+    unwrapped
 }
 
-/// Computes the Inverse Discrete Fourier Transform (IDFT) for a frequency range [k_min, k_max].
+/// Returns the local maxima of `values` (higher than the left neighbour, not lower than the right one)
+/// whose prominence is at least `min_prominence`, as (index, prominence) pairs in index order.
 ///
-/// k_min and k_max are absolute frequency indices, so both positive and negative frequencies
-/// are included by summing the coefficients at k and N-k.
-fn idft(transform: &[Complex32], k_min: usize, k_max: usize) -> Vec<Complex32> {
-    let total_points = transform.len();
-    let norm = 1.0 / (total_points as f32).sqrt();
-    let omega = Complex32::new(0.0, 2.0 * std::f32::consts::PI / total_points as f32);
+/// The prominence is the height of the peak above the higher of the minima found on each side before
+/// reaching a higher value (or the end of the data). End points are never peaks.
+pub fn find_peaks<T: Real>(values: &[T], min_prominence: T) -> Vec<(usize, T)> {
+    let mut peaks = Vec::new();
+    for i in 1..values.len().saturating_sub(1) {
+        let height = values[i];
+        if !(height > values[i - 1] && height >= values[i + 1]) { continue; }
 
-    let mut result = Vec::<Complex32>::with_capacity(total_points);
-    for i in 0..total_points {
-        let mut res = Complex32::new(0.0, 0.0);
-        let partial = omega * (i as f32);
-        for k in k_min..=k_max {
-            // Positive frequency component
-            res += transform[k] * (partial * (k as f32)).exp();
-            // Skip k=0 to avoid double counting
-            if k == 0 { continue; }
-            // The transform is symetric on 180 degrees, so we must include  negative frequency
-            res += transform[total_points - k] * (partial * (total_points - k) as f32).exp();
-        }
-        result.push(res * norm); // Unitary scaling
+        let left_base = values[..i].iter().rev()
+            .take_while(|&&v| v <= height)
+            .fold(height, |acc, &v| acc.min(v));
+        let right_base = values[i + 1..].iter()
+            .take_while(|&&v| v <= height)
+            .fold(height, |acc, &v| acc.min(v));
+        let prominence = height - left_base.max(right_base);
+        if prominence >= min_prominence {
+            peaks.push((i, prominence));
+        }
+    }
+    peaks
+}
+
+/// Unit in which spectrum frequencies are expressed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FrequencyUnit {
+    /// Frequency index k of the transform (cycles per transform length).
+    Bin,
+    /// Cycles per second; requires a sample rate.
+    Hertz,
+    /// Radians per second (2π times Hz); requires a sample rate.
+    RadiansPerSecond,
+}
+
+/// Short-time Fourier transform of a real signal.
+///
+/// The signal is cut into frames of `frame_length` samples every `hop_size` samples, each frame is
+/// multiplied by the window and transformed. Frames are centered: frame i covers the samples
+/// [i * hop - L/2, i * hop + L/2), with zeros outside the signal. Only the non-redundant half
+/// spectrum (L/2 + 1 bins) of every frame is stored.
+pub struct Stft<T: Real = f32> {
+    frame_length: usize,
+    hop_size: usize,
+    window: Window,
+    signal_length: usize,
+    // Half spectrum of every frame, frame after frame.
+    frames: Vec<Vec<Complex<T>>>,
+}
+
+impl<T: Real> Stft<T> {
+    /// Computes the STFT of real data with the given frame length, hop size and window.
+    /// Returns an error if the input is invalid, or if the hop size is zero or larger than the frame.
+    pub fn from_real(data: &[T], frame_length: usize, hop_size: usize, window: Window) -> Result<Self, String> {
+        // Validate input
+        if data.is_empty() { return Err("Input vector is empty".to_string()); }
+        if data.iter().any(|val| !val.is_finite()) {
+            return Err("Input vector contains invalid values (NaN or Inf)".to_string());
+        }
+        if frame_length < 2 { return Err("Frame length must be at least 2".to_string()); }
+        if hop_size == 0 || hop_size > frame_length {
+            return Err(format!("Hop size must be within [1, {}], got {}", frame_length, hop_size));
+        }
+        window.validate()?;
+
+        let coefficients = window.coefficients::<T>(frame_length);
+        let half = frame_length / 2;
+        // Enough frames for the last one to reach past the end of the signal
+        let num_frames = data.len().saturating_sub(frame_length - half).div_ceil(hop_size) + 1;
+
+        let mut frames = Vec::with_capacity(num_frames);
+        let mut segment = vec![T::zero(); frame_length];
+        for i in 0..num_frames {
+            for (j, val) in segment.iter_mut().enumerate() {
+                // Sample index of this frame position, if it falls inside the signal
+                let n = (i * hop_size + j).checked_sub(half).filter(|&n| n < data.len());
+                *val = n.map_or(T::zero(), |n| data[n] * coefficients[j]);
+            }
+            frames.push(rfft(&segment));
+        }
+
+        Ok(Stft { frame_length, hop_size, window, signal_length: data.len(), frames })
+    }
+
+    /// Reconstructs the signal by weighted overlap-add of the inverse transform of every frame.
+    /// Each sample is normalized by the sum of squared window values covering it, so reconstruction
+    /// is exact wherever that sum is nonzero (e.g. any hop <= L/2 with the Hann window).
+    pub fn inverse(&self) -> Vec<T> {
+        let coefficients = self.window.coefficients::<T>(self.frame_length);
+        let half = self.frame_length / 2;
+
+        let mut result = vec![T::zero(); self.signal_length];
+        let mut weights = vec![T::zero(); self.signal_length];
+        for (i, frame) in self.frames.iter().enumerate() {
+            let segment = irfft(frame, self.frame_length);
+            for (j, &val) in segment.iter().enumerate() {
+                if let Some(n) = (i * self.hop_size + j).checked_sub(half).filter(|&n| n < self.signal_length) {
+                    result[n] += val * coefficients[j];
+                    weights[n] += coefficients[j] * coefficients[j];
+                }
+            }
+        }
+
+        let epsilon: T = cast(1e-10);
+        for (val, &weight) in result.iter_mut().zip(weights.iter()) {
+            if weight > epsilon { *val /= weight; }
+        }
+        result
+    }
+
+    /// Returns the power |X|² of every frame and bin, frame after frame (num_frames x num_bins),
+    /// corrected by the coherent gain of the window.
+    pub fn power_spectrogram(&self) -> Vec<T> {
+        let gain: T = cast(self.window.coherent_gain(self.frame_length));
+        let correction = T::one() / (gain * gain);
+        self.frames.iter().flat_map(|frame| frame.iter().map(move |c| c.norm_sqr() * correction)).collect()
+    }
+
+    /// Returns the sample index at the center of every frame.
+    pub fn frame_times(&self) -> Vec<T> {
+        (0..self.frames.len()).map(|i| cast((i * self.hop_size) as f64)).collect()
+    }
+
+    /// Returns the frequency index of every bin (0..=L/2, in cycles per frame).
+    pub fn bin_frequencies(&self) -> Vec<T> {
+        (0..self.num_bins()).map(|k| cast(k as f64)).collect()
+    }
+
+    /// Returns the half spectrum of a frame.
+    pub fn frame(&self, index: usize) -> &[Complex<T>] { &self.frames[index] }
+    /// Returns the number of frames.
+    pub fn num_frames(&self) -> usize { self.frames.len() }
+    /// Returns the number of frequency bins per frame (L/2 + 1).
+    pub fn num_bins(&self) -> usize { self.frame_length / 2 + 1 }
+    /// Returns the frame length.
+    pub fn frame_length(&self) -> usize { self.frame_length }
+    /// Returns the hop size between consecutive frames.
+    pub fn hop_size(&self) -> usize { self.hop_size }
+}
+
+/// Real-to-real trigonometric transforms: discrete cosine (DCT) and sine (DST) transforms of types I to IV.
+///
+/// They correspond to DFTs of the signal extended with even (DCT) or odd (DST) symmetry, so unlike the
+/// periodic DFT extension they do not create a jump between the last and first samples. All transforms
+/// use the orthonormal scaling, so the energy of the coefficients equals the energy of the signal.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TrigKind {
+    /// Even around samples 0 and N-1; needs at least 2 samples.
+    DctI,
+    /// Even around -1/2 and N-1/2; the usual "DCT".
+    DctII,
+    /// Inverse of DCT-II.
+    DctIII,
+    /// Even around -1/2, odd around N-1/2.
+    DctIV,
+    /// Odd around -1 and N.
+    DstI,
+    /// Odd around -1/2 and N-1/2.
+    DstII,
+    /// Inverse of DST-II.
+    DstIII,
+    /// Odd around -1/2, even around N-1/2.
+    DstIV,
+}
+
+impl TrigKind {
+    /// Returns the transform undoing this one (types I and IV are their own inverse).
+    pub fn inverse(&self) -> TrigKind {
+        match *self {
+            TrigKind::DctII => TrigKind::DctIII,
+            TrigKind::DctIII => TrigKind::DctII,
+            TrigKind::DstII => TrigKind::DstIII,
+            TrigKind::DstIII => TrigKind::DstII,
+            kind => kind,
+        }
+    }
+
+    /// Sample offset a, frequency offset b and period L of the basis trig(pi (n + a) (k + b) / L),
+    /// and whether it is a sine.
+    fn basis(&self, total_points: usize) -> (f64, f64, usize, bool) {
+        match *self {
+            TrigKind::DctI => (0.0, 0.0, total_points - 1, false),
+            TrigKind::DctII => (0.5, 0.0, total_points, false),
+            TrigKind::DctIII => (0.0, 0.5, total_points, false),
+            TrigKind::DctIV => (0.5, 0.5, total_points, false),
+            TrigKind::DstI => (1.0, 1.0, total_points + 1, true),
+            TrigKind::DstII => (0.5, 1.0, total_points, true),
+            TrigKind::DstIII => (1.0, 0.5, total_points, true),
+            TrigKind::DstIV => (0.5, 0.5, total_points, true),
+        }
+    }
+
+    /// Input and output weights making the transform orthonormal.
+    fn weights(&self, total_points: usize) -> (Vec<f64>, Vec<f64>) {
+        let n = total_points;
+        let (_, _, period, _) = self.basis(n);
+        let half = std::f64::consts::FRAC_1_SQRT_2;
+        let mut input = vec![1.0; n];
+        let mut output = vec![(2.0 / period as f64).sqrt(); n];
+        match *self {
+            TrigKind::DctI => {
+                input[0] = half;
+                input[n - 1] = half;
+                output[0] *= half;
+                output[n - 1] *= half;
+            },
+            TrigKind::DctII => output[0] *= half,
+            TrigKind::DctIII => input[0] = half,
+            TrigKind::DstII => output[n - 1] *= half,
+            TrigKind::DstIII => input[n - 1] = half,
+            _ => {},
+        }
+        (input, output)
+    }
+}
+
+/// Computes the orthonormal trigonometric transform of the given kind.
+///
+/// Every kind is evaluated as a DFT of length 2L of the pre-twiddled samples, followed by a post-twiddle,
+/// so any length runs in O(N log N) through the FFT.
+/// Returns an error if the input is empty or invalid, or shorter than 2 samples for DCT-I.
+pub fn trig_transform<T: Real>(data: &[T], kind: TrigKind) -> Result<Vec<T>, String> {
+    if data.is_empty() { return Err("Input vector is empty".to_string()); }
+    if data.iter().any(|val| !val.is_finite()) {
+        return Err("Input vector contains invalid values (NaN or Inf)".to_string());
+    }
+    if kind == TrigKind::DctI && data.len() < 2 { return Err("DCT-I needs at least 2 samples".to_string()); }
+
+    let n = data.len();
+    let (sample_offset, frequency_offset, period, sine) = kind.basis(n);
+    let (input_weights, output_weights) = kind.weights(n);
+    let step = std::f64::consts::PI / period as f64;
+
+    // sum_n x[n] exp(-i pi (n + a)(k + b) / L) = exp(-i pi a (k + b) / L) DFT_2L(x[n] exp(-i pi n b / L))[k]
+    let mut extended = vec![Complex::zero(); 2 * period];
+    for (i, (&x, &w)) in data.iter().zip(input_weights.iter()).enumerate() {
+        let twiddle = Complex::from_polar(w, -step * frequency_offset * i as f64);
+        extended[i] = Complex::new(x * cast(twiddle.re), x * cast(twiddle.im));
+    }
+    let spectrum = fft_unscaled(&extended, false);
+    let result = (0..n)
+        .map(|k| {
+            let angle = -step * sample_offset * (k as f64 + frequency_offset);
+            let twiddle = Complex::new(cast::<T>(angle.cos()), cast::<T>(angle.sin()));
+            let value = spectrum[k] * twiddle;
+            // cos terms are the real part, sin terms minus the imaginary part
+            let projection = if sine { -value.im } else { value.re };
+            projection * cast(output_weights[k])
+        })
+        .collect();
+    Ok(result)
+}
+
+/// A real signal together with its DCT or DST coefficients, offering the filtering and spectrum
+/// methods of `Fourier` in that basis.
+pub struct TrigTransform<T: Real = f32> {
+    original: Vec<T>,
+    coefficients: Vec<T>,
+    kind: TrigKind,
+}
+
+impl<T: Real> TrigTransform<T> {
+    /// Constructs the transform of the given kind from real values.
+    /// Returns an error if the input is invalid (see `trig_transform`).
+    pub fn from_real(data: Vec<T>, kind: TrigKind) -> Result<Self, String> {
+        let coefficients = trig_transform(&data, kind)?;
+        Ok(TrigTransform { original: data, coefficients, kind })
+    }
+
+    /// Reconstructs the signal using only the coefficients with index in [k_min, k_max].
+    /// Returns an error if the range is invalid.
+    pub fn filtered_range(&self, k_min: usize, k_max: usize) -> Result<Vec<T>, String> {
+        let max_k = self.max_frequency();
+        if k_min > k_max || k_max > max_k {
+            return Err(format!("Frequency range [{}, {}] out of bounds (max {})", k_min, k_max, max_k));
+        }
+        self.filtered_with_mask(&SpectralMask::Range(k_min, k_max))
+    }
+
+    /// Reconstructs the signal after multiplying each coefficient by the gain of the mask,
+    /// evaluated at the coefficient index.
+    /// Returns an error if the mask parameters are invalid.
+    pub fn filtered_with_mask(&self, mask: &SpectralMask) -> Result<Vec<T>, String> {
+        mask.validate()?;
+        Ok(self.filtered_with_gain(|k| cast(mask.gain(to_f64(k)))))
+    }
+
+    /// Reconstructs the signal after multiplying each coefficient by `gain(k)`, k being its index.
+    pub fn filtered_with_gain<F: Fn(T) -> T>(&self, gain: F) -> Vec<T> {
+        let masked: Vec<T> = self.coefficients.iter().enumerate()
+            .map(|(k, &c)| c * gain(cast(k as f64)))
+            .collect();
+        // The coefficients are finite, so the inverse cannot fail
+        trig_transform(&masked, self.kind.inverse()).unwrap_or_default()
+    }
+
+    /// Returns the frequencies and the power (squared coefficient) of every basis function.
+    ///
+    /// Basis function k oscillates at (k + b) / 2L cycles per sample; frequencies are expressed in bins
+    /// of an N-point DFT (N (k + b) / 2L, about k / 2) so they are comparable with `Fourier::power_spectrum`.
+    pub fn power_spectrum(&self) -> (Vec<T>, Vec<T>) {
+        let n = self.size();
+        let (_, frequency_offset, period, _) = self.kind.basis(n);
+        let freq = (0..n).map(|k| cast(n as f64 * (k as f64 + frequency_offset) / (2 * period) as f64)).collect();
+        let powers = self.coefficients.iter().map(|&c| c * c).collect();
+        (freq, powers)
+    }
+
+    /// Returns the transform coefficients.
+    pub fn coefficients(&self) -> &[T] { &self.coefficients }
+    /// Returns the original signal.
+    pub fn original(&self) -> &[T] { &self.original }
+    /// Returns the kind of transform.
+    pub fn kind(&self) -> TrigKind { self.kind }
+    /// Returns the number of samples (and of coefficients).
+    pub fn size(&self) -> usize { self.original.len() }
+    /// Returns the highest coefficient index (N-1).
+    pub fn max_frequency(&self) -> usize { self.original.len() - 1 }
+}
+
+/// Gain applied to each frequency by `Fourier::filtered_with_mask`.
+/// Frequencies are absolute frequency indices (bins of the transform, possibly fractional).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SpectralMask {
+    /// Brick-wall band: unit gain on [k_min, k_max], zero elsewhere.
+    Range(usize, usize),
+    /// Unit gain on [k_min, k_max], falling to zero over `taper` bins on each side with a half cosine.
+    RaisedCosine(f32, f32, f32),
+    /// Magnitude of a Butterworth band-pass of the given order with -3 dB points at k_min and k_max.
+    /// A k_min of 0 gives a low-pass.
+    Butterworth(f32, f32, u32),
+    /// Gaussian centered at `center` with standard deviation `width`.
+    Gaussian(f32, f32),
+    /// Complement of a Gaussian: removes the frequencies around `center` within about `width`.
+    Notch(f32, f32),
+}
+
+impl SpectralMask {
+    /// Returns an error if the parameters are invalid.
+    pub fn validate(&self) -> Result<(), String> {
+        let check_band = |k_min: f32, k_max: f32| {
+            if k_min >= 0.0 && k_min <= k_max && k_max.is_finite() { Ok(()) }
+            else { Err(format!("Invalid frequency band [{}, {}]", k_min, k_max)) }
+        };
+        let check_width = |width: f32| {
+            if width > 0.0 && width.is_finite() { Ok(()) }
+            else { Err(format!("Mask width must be positive, got {}", width)) }
+        };
+        match *self {
+            SpectralMask::Range(k_min, k_max) if k_min > k_max =>
+                Err(format!("Invalid frequency range [{}, {}]", k_min, k_max)),
+            SpectralMask::Range(_, _) => Ok(()),
+            SpectralMask::RaisedCosine(k_min, k_max, taper) => {
+                check_band(k_min, k_max)?;
+                if taper >= 0.0 && taper.is_finite() { Ok(()) }
+                else { Err(format!("Taper width must be non-negative, got {}", taper)) }
+            },
+            SpectralMask::Butterworth(k_min, k_max, order) => {
+                check_band(k_min, k_max)?;
+                if k_max <= 0.0 { return Err("Butterworth mask needs a positive upper edge".to_string()); }
+                if order == 0 { return Err("Butterworth mask order must be positive".to_string()); }
+                Ok(())
+            },
+            SpectralMask::Gaussian(center, width) | SpectralMask::Notch(center, width) => {
+                if !center.is_finite() { return Err(format!("Invalid mask center {}", center)); }
+                check_width(width)
+            },
+        }
+    }
+
+    /// Returns the gain at the absolute frequency index k.
+    pub fn gain(&self, k: f64) -> f64 {
+        match *self {
+            SpectralMask::Range(k_min, k_max) => if k >= k_min as f64 && k <= k_max as f64 { 1.0 } else { 0.0 },
+            SpectralMask::RaisedCosine(k_min, k_max, taper) => {
+                let (k_min, k_max, taper) = (k_min as f64, k_max as f64, taper as f64);
+                // Distance outside of the band
+                let distance = (k_min - k).max(k - k_max);
+                if distance <= 0.0 { 1.0 }
+                else if distance >= taper { 0.0 }
+                else { 0.5 * (1.0 + (std::f64::consts::PI * distance / taper).cos()) }
+            },
+            SpectralMask::Butterworth(k_min, k_max, order) => {
+                let exponent = 2 * order as i32;
+                let lowpass = 1.0 / (1.0 + (k / k_max as f64).powi(exponent)).sqrt();
+                let highpass = if k_min <= 0.0 { 1.0 } else { 1.0 / (1.0 + (k_min as f64 / k).powi(exponent)).sqrt() };
+                lowpass * highpass
+            },
+            SpectralMask::Gaussian(center, width) => {
+                let x = (k - center as f64) / width as f64;
+                (-0.5 * x * x).exp()
+            },
+            SpectralMask::Notch(center, width) => {
+                let x = (k - center as f64) / width as f64;
+                1.0 - (-0.5 * x * x).exp()
+            },
+        }
+    }
+}
+
+/// Which frequencies a power spectral density estimate covers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SpectrumSides {
+    /// Frequencies 0..=fs/2, with the negative-frequency power folded onto the positive side.
+    OneSided,
+    /// Frequencies -fs/2..fs/2, fftshifted (zero frequency centered).
+    TwoSided,
+}
+
+/// Welch's power spectral density estimate of real data.
+///
+/// The signal is split into segments of `segment_length` samples overlapping by `overlap` samples,
+/// each segment is windowed and its periodogram computed, and the periodograms are averaged.
+/// Returns the frequencies in Hz and the density in units²/Hz for the given sample rate, so that
+/// integrating the density over frequency gives the mean power of the signal.
+/// Returns an error if the input is invalid or the segmentation does not fit the signal.
+pub fn welch<T: Real>(data: &[T], sample_rate: T, segment_length: usize, overlap: usize, window: Window, sides: SpectrumSides) -> Result<(Vec<T>, Vec<T>), String> {
+    // Validate input
+    if data.is_empty() { return Err("Input vector is empty".to_string()); }
+    if data.iter().any(|val| !val.is_finite()) {
+        return Err("Input vector contains invalid values (NaN or Inf)".to_string());
+    }
+    if !sample_rate.is_finite() || sample_rate <= T::zero() {
+        return Err(format!("Sample rate must be a positive number, got {}", sample_rate));
+    }
+    if segment_length < 2 || segment_length > data.len() {
+        return Err(format!("Segment length must be within [2, {}], got {}", data.len(), segment_length));
+    }
+    if overlap >= segment_length {
+        return Err(format!("Overlap ({}) must be smaller than the segment length ({})", overlap, segment_length));
+    }
+    window.validate()?;
+
+    let coefficients = window.coefficients::<T>(segment_length);
+    let step = segment_length - overlap;
+    let num_segments = (data.len() - segment_length) / step + 1;
+
+    // Average the half-spectrum periodograms of all segments
+    let num_bins = segment_length / 2 + 1;
+    let mut average = vec![T::zero(); num_bins];
+    let mut segment = vec![T::zero(); segment_length];
+    for i in 0..num_segments {
+        let start = i * step;
+        for (j, val) in segment.iter_mut().enumerate() {
+            *val = data[start + j] * coefficients[j];
+        }
+        for (acc, c) in average.iter_mut().zip(rfft(&segment)) {
+            *acc += c.norm_sqr();
+        }
+    }
+
+    // The unitary transform gives |X|² = |sum w x e|² / L, so the density is L |X|² / (fs sum(w²))
+    let window_power = coefficients.iter().fold(T::zero(), |acc, &w| acc + w * w);
+    let scale = cast::<T>(segment_length as f64) / (sample_rate * window_power * cast(num_segments as f64));
+    let bin_width = sample_rate / cast(segment_length as f64);
+
+    match sides {
+        SpectrumSides::OneSided => {
+            let freq = (0..num_bins).map(|k| bin_width * cast(k as f64)).collect();
+            let density = average.iter().enumerate()
+                .map(|(k, &p)| {
+                    // Every bin except DC and Nyquist also stands for its negative frequency
+                    let folded = k != 0 && 2 * k != segment_length;
+                    p * scale * if folded { cast(2.0) } else { T::one() }
+                })
+                .collect();
+            Ok((freq, density))
+        },
+        SpectrumSides::TwoSided => {
+            let first = -cast::<T>((segment_length / 2) as f64);
+            let freq = (0..segment_length).map(|i| bin_width * (first + cast(i as f64))).collect();
+            let density = (0..segment_length)
+                .map(|i| {
+                    // Shifted index i maps to frequency k = i - L/2, stored at |k| by Hermitian symmetry
+                    let k = (i as isize - (segment_length / 2) as isize).unsigned_abs();
+                    average[k] * scale
+                })
+                .collect();
+            Ok((freq, density))
+        },
+    }
+}
+
+/// Bartlett's power spectral density estimate: Welch's method with rectangular,
+/// non-overlapping segments. See `welch` for the units and errors.
+pub fn bartlett<T: Real>(data: &[T], sample_rate: T, segment_length: usize, sides: SpectrumSides) -> Result<(Vec<T>, Vec<T>), String> {
+    welch(data, sample_rate, segment_length, 0, Window::Rectangular, sides)
+}
+
+/// Output extent of `convolve`, `correlate` and `autocorrelate`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConvolutionMode {
+    /// Every overlap of the two inputs (length N + M - 1).
+    Full,
+    /// The central part of the full output with the length of the first input.
+    Same,
+    /// Only the outputs where the inputs overlap completely (length |N - M| + 1).
+    Valid,
+    /// Periodic convolution over max(N, M) samples, the shorter input being zero-padded.
+    Circular,
+}
+
+/// Convolves two real signals through the transform, zero-padding them to avoid wrap-around
+/// (except in circular mode).
+/// Returns an error if either input is empty or contains invalid values.
+pub fn convolve<T: Real>(a: &[T], b: &[T], mode: ConvolutionMode) -> Result<Vec<T>, String> {
+    validate_signal(a)?;
+    validate_signal(b)?;
+    if mode == ConvolutionMode::Circular {
+        let total_points = a.len().max(b.len());
+        return Ok(spectral_product(a, b, total_points, false));
+    }
+
+    let full_length = a.len() + b.len() - 1;
+    let full = spectral_product(a, b, full_length.next_power_of_two(), false);
+    Ok(linear_extent(full, a.len(), b.len(), mode))
+}
+
+/// Cross-correlates two real signals: r[k] = sum_n a[n + k] b[n].
+///
+/// In full mode the output covers the lags -(M - 1)..=N - 1 in order, `Same` and `Valid` keep the same
+/// central parts as `convolve`, and circular mode returns the lags 0..max(N, M) taken modulo max(N, M).
+/// Returns an error if either input is empty or contains invalid values.
+pub fn correlate<T: Real>(a: &[T], b: &[T], mode: ConvolutionMode) -> Result<Vec<T>, String> {
+    validate_signal(a)?;
+    validate_signal(b)?;
+    if mode == ConvolutionMode::Circular {
+        let total_points = a.len().max(b.len());
+        return Ok(spectral_product(a, b, total_points, true));
+    }
+
+    // Correlation is the convolution with the time-reversed second signal
+    let reversed: Vec<T> = b.iter().rev().cloned().collect();
+    convolve(a, &reversed, mode)
+}
+
+/// Autocorrelation of a real signal, i.e. `correlate(data, data, mode)`.
+/// In full mode the zero lag is at index N - 1; in circular mode it is at index 0.
+pub fn autocorrelate<T: Real>(data: &[T], mode: ConvolutionMode) -> Result<Vec<T>, String> {
+    correlate(data, data, mode)
+}
+
+/// Estimates the period of a real signal, in samples, from its autocorrelation.
+///
+/// The mean is removed, and the period is the lag of the highest autocorrelation peak after the first zero
+/// crossing, refined between lags by a parabola through the peak and its neighbours. Only lags up to N/2 are
+/// searched, so at least two periods must be present. Returns `None` if the signal is constant or shorter than
+/// 4 samples, or if the peak is below `min_correlation` times the zero-lag value (the signal is not periodic).
+/// Returns an error if the input is empty or contains invalid values.
+pub fn estimate_period<T: Real>(data: &[T], min_correlation: T) -> Result<Option<T>, String> {
+    validate_signal(data)?;
+    let n = data.len();
+    if n < 4 { return Ok(None); }
+    let mean = data.iter().map(|&x| to_f64(x)).sum::<f64>() / n as f64;
+    let centered: Vec<f64> = data.iter().map(|&x| to_f64(x) - mean).collect();
+    let energy: f64 = data.iter().map(|&x| to_f64(x) * to_f64(x)).sum();
+
+    // Lags 0..N of the full autocorrelation; a constant signal leaves only rounding errors
+    let full = autocorrelate(&centered, ConvolutionMode::Full)?;
+    let r = &full[n - 1..];
+    if r[0] <= 1e-12 * energy { return Ok(None); }
+
+    // Skip the main lobe around lag 0, then take the highest local maximum
+    let last = n / 2;
+    let start = match (1..=last).find(|&lag| r[lag] < 0.0) {
+        Some(lag) => lag,
+        None => return Ok(None),
+    };
+    let peak = (start..=last).max_by(|&a, &b| r[a].total_cmp(&r[b])).unwrap_or(start);
+    if r[peak] < to_f64(min_correlation) * r[0] || r[peak] < r[peak + 1] { return Ok(None); }
+
+    let (previous, center, next) = (r[peak - 1], r[peak], r[peak + 1]);
+    let denominator = previous - 2.0 * center + next;
+    let offset = if denominator.abs() < f64::EPSILON * r[0] { 0.0 } else { 0.5 * (previous - next) / denominator };
+    Ok(Some(cast(peak as f64 + offset.clamp(-0.5, 0.5))))
+}
+
+/// Streaming convolution with a fixed kernel by the overlap-add method.
+///
+/// Input is consumed in blocks of `block_length` samples, each convolved through the transform; the
+/// tail of every block is added to the start of the next one. Concatenating the outputs of `process`
+/// and `finish` gives the full linear convolution.
+pub struct OverlapAdd<T: Real = f32> {
+    kernel_spectrum: Vec<Complex<T>>,
+    kernel_length: usize,
+    block_length: usize,
+    total_points: usize,
+    // Last kernel_length - 1 samples of the previous block, still to be added to the output
+    tail: Vec<T>,
+}
+
+impl<T: Real> OverlapAdd<T> {
+    /// Prepares the convolution with `kernel` for input blocks of at most `block_length` samples.
+    /// Returns an error if the kernel is empty or invalid, or the block length is zero.
+    pub fn new(kernel: &[T], block_length: usize) -> Result<Self, String> {
+        validate_signal(kernel)?;
+        if block_length == 0 { return Err("Block length must be positive".to_string()); }
+
+        let total_points = (block_length + kernel.len() - 1).next_power_of_two();
+        let mut padded = kernel.to_vec();
+        padded.resize(total_points, T::zero());
+        Ok(OverlapAdd {
+            kernel_spectrum: rfft(&padded),
+            kernel_length: kernel.len(),
+            block_length,
+            total_points,
+            tail: vec![T::zero(); kernel.len() - 1],
+        })
+    }
+
+    /// Convolves the next chunk of the input (of any length) and returns as many output samples.
+    pub fn process(&mut self, input: &[T]) -> Vec<T> {
+        let mut output = Vec::with_capacity(input.len());
+        // The unitary transforms leave a factor sqrt(L) on the product of spectra
+        let scale = cast::<T>(self.total_points as f64).sqrt();
+        for block in input.chunks(self.block_length) {
+            let mut padded = block.to_vec();
+            padded.resize(self.total_points, T::zero());
+            let product: Vec<Complex<T>> = rfft(&padded).iter().zip(self.kernel_spectrum.iter())
+                .map(|(&x, &h)| x * h * scale)
+                .collect();
+            let mut result = irfft(&product, self.total_points);
+
+            for (y, &t) in result.iter_mut().zip(self.tail.iter()) {
+                *y += t;
+            }
+            let end = block.len() + self.kernel_length - 1;
+            self.tail = result[block.len()..end].to_vec();
+            output.extend_from_slice(&result[..block.len()]);
+        }
+        output
+    }
+
+    /// Returns the last kernel_length - 1 output samples and resets the stream.
+    pub fn finish(&mut self) -> Vec<T> {
+        std::mem::replace(&mut self.tail, vec![T::zero(); self.kernel_length - 1])
+    }
+}
+
+/// Product of the spectra of two real signals zero-padded to `total_points`, transformed back.
+/// With `conjugate`, the second spectrum is conjugated, giving the circular cross-correlation.
+fn spectral_product<T: Real>(a: &[T], b: &[T], total_points: usize, conjugate: bool) -> Vec<T> {
+    let transform = |data: &[T]| {
+        let mut padded = data.to_vec();
+        padded.resize(total_points, T::zero());
+        rfft(&padded)
+    };
+    // The unitary transforms leave a factor sqrt(L) on the product of spectra
+    let scale = cast::<T>(total_points as f64).sqrt();
+    let product: Vec<Complex<T>> = transform(a).iter().zip(transform(b).iter())
+        .map(|(&x, &y)| x * if conjugate { y.conj() } else { y } * scale)
+        .collect();
+    irfft(&product, total_points)
+}
+
+/// Cuts the requested part out of a full linear convolution of signals of lengths n and m
+/// (the full result may carry extra padding at the end).
+fn linear_extent<T: Real>(mut full: Vec<T>, n: usize, m: usize, mode: ConvolutionMode) -> Vec<T> {
+    let (start, length) = match mode {
+        ConvolutionMode::Same => ((m - 1) / 2, n),
+        ConvolutionMode::Valid => (n.min(m) - 1, n.max(m) - n.min(m) + 1),
+        _ => (0, n + m - 1),
+    };
+    full.truncate(start + length);
+    full.drain(..start);
+    full
+}
+
+/// Returns an error if the signal is empty or contains NaN or Inf values.
+pub(crate) fn validate_signal<T: Real>(data: &[T]) -> Result<(), String> {
+    if data.is_empty() { return Err("Input vector is empty".to_string()); }
+    if data.iter().any(|val| !val.is_finite()) {
+        return Err("Input vector contains invalid values (NaN or Inf)".to_string());
+    }
+    Ok(())
+}
+
+/// Zero-padding applied to a signal before the DFT.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Padding {
+    /// Transform exactly the input samples.
+    None,
+    /// Pad with zeros up to the given total length, which must not be shorter than the signal.
+    ToLength(usize),
+    /// Pad with zeros up to the next power of two.
+    NextPowerOfTwo,
+}
+
+impl Padding {
+    /// Returns the transform length for a signal of the given length.
+    pub fn padded_length(&self, signal_length: usize) -> Result<usize, String> {
+        match *self {
+            Padding::None => Ok(signal_length),
+            Padding::ToLength(length) if length < signal_length => {
+                Err(format!("Padded length {} is shorter than the signal ({} points)", length, signal_length))
+            },
+            Padding::ToLength(length) => Ok(length),
+            Padding::NextPowerOfTwo => Ok(signal_length.next_power_of_two()),
+        }
+    }
+}
+
+/// Window functions applied to a signal before the DFT to reduce spectral leakage.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Window {
+    Rectangular,
+    Hann,
+    Hamming,
+    Blackman,
+    /// Kaiser window with shape parameter beta >= 0 (larger beta: lower sidelobes, wider main lobe).
+    Kaiser(f32),
+    /// Flat-top window, for accurate amplitude readings of sinusoids between bins.
+    FlatTop,
+    /// Tukey (tapered cosine) window; alpha in [0, 1] is the tapered fraction (0 = rectangular, 1 = Hann).
+    Tukey(f32),
+}
+
+impl Window {
+    /// Returns an error if the window parameter is out of range.
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            Window::Kaiser(beta) if !beta.is_finite() || beta < 0.0 => {
+                Err(format!("Kaiser beta must be a non-negative number, got {}", beta))
+            },
+            Window::Tukey(alpha) if !(0.0..=1.0).contains(&alpha) => {
+                Err(format!("Tukey alpha must be within [0, 1], got {}", alpha))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the N window coefficients.
+    /// Windows are periodic (DFT-even), which is the appropriate form for spectral analysis.
+    pub fn coefficients<T: Real>(&self, total_points: usize) -> Vec<T> {
+        let n = total_points as f64;
+        let cosine_sum = |a: &[f64], x: f64| {
+            a.iter().enumerate()
+                .map(|(j, &aj)| {
+                    let sign = if j % 2 == 0 { 1.0 } else { -1.0 };
+                    sign * aj * (2.0 * std::f64::consts::PI * j as f64 * x).cos()
+                })
+                .sum::<f64>()
+        };
+
+        (0..total_points)
+            .map(|i| {
+                let x = i as f64 / n; // Position within the period, in [0, 1)
+                let w = match *self {
+                    Window::Rectangular => 1.0,
+                    Window::Hann => cosine_sum(&[0.5, 0.5], x),
+                    Window::Hamming => cosine_sum(&[0.54, 0.46], x),
+                    Window::Blackman => cosine_sum(&[0.42, 0.5, 0.08], x),
+                    Window::FlatTop => cosine_sum(&[0.21557895, 0.41663158, 0.277263158, 0.083578947, 0.006947368], x),
+                    Window::Kaiser(beta) => {
+                        let r = 2.0 * x - 1.0;
+                        bessel_i0(beta as f64 * (1.0 - r * r).sqrt()) / bessel_i0(beta as f64)
+                    },
+                    Window::Tukey(alpha) => {
+                        let alpha = alpha as f64;
+                        let edge = x.min(1.0 - x); // Distance to the closest end of the period
+                        if alpha <= 0.0 || edge >= alpha / 2.0 { 1.0 }
+                        else { 0.5 * (1.0 - (2.0 * std::f64::consts::PI * edge / alpha).cos()) }
+                    },
+                };
+                cast(w)
+            })
+            .collect()
+    }
+
+    /// Returns the coherent gain (mean coefficient), i.e. the amplitude scaling of a sinusoid.
+    pub fn coherent_gain(&self, total_points: usize) -> f64 {
+        let coefficients = self.coefficients::<f64>(total_points);
+        coefficients.iter().sum::<f64>() / total_points.max(1) as f64
+    }
+
+    /// Returns the half width of the main lobe in bins, beyond which the leakage of a tone is small.
+    fn main_lobe_half_width(&self) -> usize {
+        match *self {
+            Window::Rectangular => 1,
+            Window::Hann | Window::Hamming => 2,
+            Window::Blackman => 3,
+            Window::FlatTop => 5,
+            Window::Kaiser(beta) => (1.0 + (beta as f64 / std::f64::consts::PI).powi(2)).sqrt().ceil() as usize,
+            Window::Tukey(alpha) => if alpha > 0.0 { 2 } else { 1 },
+        }
+    }
+
+    /// Returns the equivalent noise bandwidth in bins: N * sum(w²) / sum(w)².
+    pub fn enbw(&self, total_points: usize) -> f64 {
+        let coefficients = self.coefficients::<f64>(total_points);
+        let sum = coefficients.iter().sum::<f64>();
+        let sum_sqr = coefficients.iter().map(|w| w * w).sum::<f64>();
+        total_points as f64 * sum_sqr / (sum * sum)
+    }
+}
+
+/// Modified Bessel function of the first kind, order zero (power series).
+fn bessel_i0(x: f64) -> f64 {
+    let quarter_sqr = x * x / 4.0;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    let mut k = 1.0;
+    while term > sum * 1e-12 {
+        term *= quarter_sqr / (k * k);
+        sum += term;
+        k += 1.0;
+    }
+    sum
+}
+
+/// Computes the chirp-z transform of the input data on `points` frequencies.
+///
+/// Evaluates X_k = sum_n x_n exp(-i (start_angle + k * step_angle) n) for k in 0..points,
+/// i.e. the z-transform along an arc of the unit circle starting at `start_angle` radians
+/// with spacing `step_angle` radians. Uses the same unitary 1/sqrt(N) scaling as `Fourier`,
+/// so with start_angle = 0 and step_angle = 2π/N it reproduces the DFT coefficients.
+/// Runs in O((N + points) log(N + points)) through Bluestein's algorithm.
+pub fn chirp_z<T: Real>(data: &[Complex<T>], points: usize, start_angle: T, step_angle: T) -> Result<Vec<Complex<T>>, String> {
+    if data.is_empty() { return Err("Input vector is empty".to_string()); }
+    if points == 0 { return Err("Number of output points must be positive".to_string()); }
+    if !start_angle.is_finite() || !step_angle.is_finite() {
+        return Err("Chirp-z angles must be finite".to_string());
+    }
+
+    let norm = T::one() / cast::<T>(data.len() as f64).sqrt();
+    let mut result = chirp_z_unscaled(data, points, to_f64(start_angle), to_f64(step_angle));
+    for val in result.iter_mut() {
+        *val *= norm; // Unitary scaling
+    }
+    Ok(result)
+}
+
+/// Zoom FFT: evaluates the spectrum on `points` evenly spaced frequencies in [k_start, k_end].
+///
+/// Frequencies are expressed in (possibly fractional) bin units, so integer values coincide
+/// with the regular DFT coefficients and negative values address negative frequencies.
+/// This gives an arbitrarily fine view of a sub-band without transforming a longer signal.
+pub fn zoom_fft<T: Real>(data: &[Complex<T>], k_start: T, k_end: T, points: usize) -> Result<Vec<Complex<T>>, String> {
+    if points < 2 { return Err("Zoom FFT requires at least two output points".to_string()); }
+    if !k_start.is_finite() || !k_end.is_finite() || k_start >= k_end {
+        return Err(format!("Invalid zoom range [{}, {}]", k_start, k_end));
+    }
+
+    let bin_angle = T::TAU() / cast(data.len().max(1) as f64);
+    let step = (k_end - k_start) / cast((points - 1) as f64);
+    chirp_z(data, points, k_start * bin_angle, step * bin_angle)
+}
+
+/// Computes the unitary Discrete Fourier Transform of the input data using the FFT.
+/// If `inverse` is true, the inverse transform (positive exponent) is computed instead.
+pub(crate) fn fft<T: Real>(data: &[Complex<T>], inverse: bool) -> Vec<Complex<T>> {
+    let norm = T::one() / cast::<T>(data.len() as f64).sqrt();
+    let mut result = fft_unscaled(data, inverse);
+    for val in result.iter_mut() {
+        *val *= norm; // Unitary scaling
+    }
+    result
+}
+
+/// Unnormalized FFT of any length.
+///
+/// Power-of-two lengths use the in-place radix-2 algorithm. Other lengths use a recursive
+/// mixed-radix Cooley-Tukey decomposition over their prime factors, unless a prime factor is
+/// larger than `BLUESTEIN_THRESHOLD`, in which case Bluestein's chirp-z algorithm is used.
+fn fft_unscaled<T: Real>(data: &[Complex<T>], inverse: bool) -> Vec<Complex<T>> {
+    let total_points = data.len();
+
+    if total_points.is_power_of_two() {
+        let mut buffer = data.to_vec();
+        radix2(&mut buffer, &twiddle_factors(total_points, inverse));
+        buffer
+    } else if largest_factor(total_points) <= BLUESTEIN_THRESHOLD {
+        let mut buffer = vec![Complex::zero(); total_points];
+        mixed_radix(data, &twiddle_factors(total_points, inverse), 1, &mut buffer);
+        buffer
+    } else {
+        // Large prime factors make mixed-radix quadratic, so evaluate the DFT as a chirp-z transform
+        let sign = if inverse { -1.0 } else { 1.0 };
+        chirp_z_unscaled(data, total_points, 0.0, sign * 2.0 * std::f64::consts::PI / total_points as f64)
+    }
+}
+
+/// Computes the non-redundant half (bins 0..=N/2) of the unitary DFT of real data.
+///
+/// For even N, the samples are packed as z[j] = x[2j] + i x[2j+1] and a single complex FFT
+/// of length N/2 is run, after which the even and odd spectra are separated and recombined.
+/// Odd lengths fall back to the full complex transform.
+fn rfft<T: Real>(data: &[T]) -> Vec<Complex<T>> {
+    let total_points = data.len();
+    if total_points % 2 == 1 {
+        let widened: Vec<Complex<T>> = data.iter().map(|&x| Complex::new(x, T::zero())).collect();
+        let mut full = fft(&widened, false);
+        full.truncate(total_points / 2 + 1);
+        return full;
+    }
+
+    let half = total_points / 2;
+    let packed: Vec<Complex<T>> = (0..half).map(|j| Complex::new(data[2 * j], data[2 * j + 1])).collect();
+    let spectrum = fft_unscaled(&packed, false);
+    let twiddles = twiddle_factors::<T>(total_points, false);
+    let norm = T::one() / cast::<T>(total_points as f64).sqrt();
+    let half_unit: T = cast(0.5);
+
+    (0..=half)
+        .map(|k| {
+            let a = spectrum[k % half];
+            let b = spectrum[(half - k) % half].conj();
+            let even = (a + b) * half_unit;
+            let odd = (a - b) * Complex::new(T::zero(), -half_unit);
+            (even + twiddles[k] * odd) * norm // Unitary scaling
+        })
+        .collect()
+}
+
+/// Inverse of `rfft`: rebuilds N real samples from the half spectrum (bins 0..=N/2).
+fn irfft<T: Real>(half_spectrum: &[Complex<T>], total_points: usize) -> Vec<T> {
+    if total_points % 2 == 1 {
+        let full: Vec<Complex<T>> = (0..total_points)
+            .map(|k| if k < half_spectrum.len() { half_spectrum[k] } else { half_spectrum[total_points - k].conj() })
+            .collect();
+        return fft(&full, true).iter().map(|c| c.re).collect();
+    }
+
+    // Undo the even/odd split to recover the spectrum of the packed sequence
+    let half = total_points / 2;
+    let twiddles = twiddle_factors::<T>(total_points, true);
+    let half_unit: T = cast(0.5);
+    let packed: Vec<Complex<T>> = (0..half)
+        .map(|k| {
+            let a = half_spectrum[k];
+            let b = half_spectrum[half - k].conj();
+            let even = (a + b) * half_unit;
+            let odd = (a - b) * half_unit * twiddles[k];
+            even + Complex::<T>::i() * odd
+        })
+        .collect();
+
+    // Unitary scaling: sqrt(N) to undo the forward normalization, 1/(N/2) for the inverse FFT
+    let scale = cast::<T>(2.0) / cast::<T>(total_points as f64).sqrt();
+    let mut result = Vec::<T>::with_capacity(total_points);
+    for val in fft_unscaled(&packed, true) {
+        result.push(val.re * scale);
+        result.push(val.im * scale);
+    }
+    result
+}
+
+/// Precomputes the twiddle factors exp(∓2πik/N) for k in 0..N.
+/// Angles are evaluated in double precision to keep round-off low for large N.
+fn twiddle_factors<T: Real>(total_points: usize, inverse: bool) -> Vec<Complex<T>> {
+    let sign = if inverse { 1.0 } else { -1.0 };
+    (0..total_points)
+        .map(|k| {
+            let angle = sign * 2.0 * std::f64::consts::PI * (k as f64) / (total_points as f64);
+            Complex::new(cast(angle.cos()), cast(angle.sin()))
+        })
+        .collect()
+}
+
+/// In-place iterative radix-2 Cooley-Tukey FFT (unnormalized).
+/// The buffer length must be a power of two and match the twiddle table.
+fn radix2<T: Real>(buffer: &mut [Complex<T>], twiddles: &[Complex<T>]) {
+    let total_points = buffer.len();
+    if total_points < 2 { return; }
+
+    // Bit-reversal permutation
+    let bits = total_points.trailing_zeros();
+    for i in 0..total_points {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if i < j { buffer.swap(i, j); }
+    }
+
+    // Butterfly stages, doubling the sub-transform length each time
+    let mut len = 2;
+    while len <= total_points {
+        let half = len / 2;
+        let step = total_points / len;
+        for start in (0..total_points).step_by(len) {
+            for j in 0..half {
+                let a = buffer[start + j];
+                let b = buffer[start + j + half] * twiddles[j * step];
+                buffer[start + j] = a + b;
+                buffer[start + j + half] = a - b;
+            }
+        }
+        len *= 2;
+    }
+}
+
+/// Recursive mixed-radix Cooley-Tukey FFT (unnormalized, decimation in time).
+///
+/// Transforms the elements `data[0], data[stride], ...` into `output`, whose length sets the
+/// size of this sub-transform. The length is split as p * m with p its smallest prime factor,
+/// so the cost is O(N * sum of prime factors).
+fn mixed_radix<T: Real>(data: &[Complex<T>], twiddles: &[Complex<T>], stride: usize, output: &mut [Complex<T>]) {
+    let total_points = output.len();
+    if total_points == 1 {
+        output[0] = data[0];
+        return;
+    }
+
+    let radix = smallest_factor(total_points);
+    let sub_points = total_points / radix;
+
+    // Transform each decimated subsequence into its own block of the output
+    for r in 0..radix {
+        let block = &mut output[r * sub_points..(r + 1) * sub_points];
+        mixed_radix(&data[r * stride..], twiddles, stride * radix, block);
+    }
+
+    // Combine the blocks: X[k + q*m] = sum_r W_N^(r*(k + q*m)) * F_r[k]
+    let twiddle_step = twiddles.len() / total_points;
+    let mut scratch = vec![Complex::zero(); radix];
+    for k in 0..sub_points {
+        for r in 0..radix {
+            scratch[r] = output[r * sub_points + k];
+        }
+        for q in 0..radix {
+            let idx = k + q * sub_points;
+            let mut res = Complex::zero();
+            let mut exponent = 0;
+            for &val in &scratch {
+                res += val * twiddles[exponent * twiddle_step];
+                exponent = (exponent + idx) % total_points;
+            }
+            output[idx] = res;
+        }
+    }
+}
+
+/// Returns the smallest prime factor of n (n itself if n is prime).
+fn smallest_factor(n: usize) -> usize {
+    if n.is_multiple_of(2) { return 2; }
+    let mut factor = 3;
+    while factor * factor <= n {
+        if n.is_multiple_of(factor) { return factor; }
+        factor += 2;
+    }
+    n
+}
+
+/// Returns the largest prime factor of n.
+fn largest_factor(n: usize) -> usize {
+    let mut remaining = n;
+    let mut largest = 1;
+    while remaining > 1 {
+        largest = smallest_factor(remaining);
+        remaining /= largest;
+    }
+    largest
+}
+
+/// Bluestein's chirp-z algorithm (unnormalized).
+///
+/// Rewrites n*k = (n² + k² - (k-n)²) / 2 so that the transform becomes a convolution with
+/// the chirp exp(i * step_angle * j² / 2), which is evaluated with power-of-two FFTs.
+fn chirp_z_unscaled<T: Real>(data: &[Complex<T>], points: usize, start_angle: f64, step_angle: f64) -> Vec<Complex<T>> {
+    let total_points = data.len();
+    // Chirp angles are computed in double precision, since j² grows quickly
+    let chirp = |j: usize| {
+        let angle = -0.5 * step_angle * (j as f64) * (j as f64);
+        Complex::new(cast::<T>(angle.cos()), cast::<T>(angle.sin()))
+    };
+
+    let conv_len = (total_points + points - 1).next_power_of_two();
+    let forward = twiddle_factors::<T>(conv_len, false);
+    let backward = twiddle_factors::<T>(conv_len, true);
+
+    // Pre-multiply the input by the starting phase and the chirp
+    let mut signal = vec![Complex::zero(); conv_len];
+    for (n, &val) in data.iter().enumerate() {
+        let angle = -start_angle * n as f64;
+        signal[n] = val * Complex::new(cast::<T>(angle.cos()), cast::<T>(angle.sin())) * chirp(n);
+    }
+
+    // Conjugate chirp filter, wrapped around for negative lags
+    let mut filter = vec![Complex::zero(); conv_len];
+    for j in 0..points.max(total_points) {
+        let val = chirp(j).conj();
+        if j < points { filter[j] = val; }
+        if j > 0 && j < total_points { filter[conv_len - j] = val; }
+    }
+
+    // Circular convolution through the FFT
+    radix2(&mut signal, &forward);
+    radix2(&mut filter, &forward);
+    for (a, &b) in signal.iter_mut().zip(filter.iter()) {
+        *a *= b;
+    }
+    radix2(&mut signal, &backward);
+
+    let scale = T::one() / cast::<T>(conv_len as f64);
+    (0..points).map(|k| signal[k] * chirp(k) * scale).collect()
+}
+
+/// Converts a double precision value to the working precision.
+pub(crate) fn cast<T: Real>(x: f64) -> T {
+    <T as num_traits::NumCast>::from(x).unwrap()
+}
+
+/// Converts a working precision value to double precision.
+pub(crate) fn to_f64<T: Real>(x: T) -> f64 {
+    x.to_f64().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Lengths covering the radix-2 (1, 2, 128), mixed-radix (3, 12) and Bluestein (97, 131) paths.
+    const LENGTHS: [usize; 7] = [1, 2, 3, 12, 97, 128, 131];
+
+    /// Unitary DFT evaluated directly from its definition.
+    fn naive_dft(data: &[Complex<f64>], inverse: bool) -> Vec<Complex<f64>> {
+        let n = data.len();
+        let sign = if inverse { 1.0 } else { -1.0 };
+        (0..n)
+            .map(|k| {
+                let sum: Complex<f64> = data.iter().enumerate()
+                    .map(|(j, &x)| x * Complex::from_polar(1.0, sign * 2.0 * std::f64::consts::PI * ((j * k) % n) as f64 / n as f64))
+                    .sum();
+                sum / (n as f64).sqrt()
+            })
+            .collect()
+    }
+
+    /// Deterministic test signal with distinct real and imaginary parts.
+    fn test_signal(n: usize) -> Vec<Complex<f64>> {
+        (0..n).map(|j| Complex::new((0.7 * j as f64).sin() + 0.1 * j as f64, (1.3 * j as f64).cos())).collect()
+    }
+
+    fn assert_close(actual: &[Complex<f64>], expected: &[Complex<f64>], n: usize) {
+        assert_eq!(actual.len(), expected.len(), "N = {}", n);
+        for (k, (a, e)) in actual.iter().zip(expected.iter()).enumerate() {
+            assert!((a - e).norm() < 1e-9, "N = {}, bin {}: {} != {}", n, k, a, e);
+        }
+    }
+
+    #[test]
+    fn fft_matches_naive_dft() {
+        for n in LENGTHS {
+            let data = test_signal(n);
+            assert_close(&fft(&data, false), &naive_dft(&data, false), n);
+            assert_close(&fft(&data, true), &naive_dft(&data, true), n);
+        }
+    }
+
+    #[test]
+    fn rfft_matches_half_of_naive_dft() {
+        for n in LENGTHS {
+            let real: Vec<f64> = test_signal(n).iter().map(|c| c.re).collect();
+            let widened: Vec<Complex<f64>> = real.iter().map(|&x| Complex::new(x, 0.0)).collect();
+            let half = rfft(&real);
+            assert_close(&half, &naive_dft(&widened, false)[..n / 2 + 1], n);
+            let restored = irfft(&half, n);
+            for (a, e) in restored.iter().zip(real.iter()) {
+                assert!((a - e).abs() < 1e-9, "N = {}: {} != {}", n, a, e);
+            }
+        }
+    }
+
+    #[test]
+    fn fft_is_unitary() {
+        for n in LENGTHS {
+            let data = test_signal(n);
+            let energy: f64 = data.iter().map(|c| c.norm_sqr()).sum();
+            let spectrum = fft(&data, false);
+            let spectral_energy: f64 = spectrum.iter().map(|c| c.norm_sqr()).sum();
+            assert!((energy - spectral_energy).abs() < 1e-9 * energy, "N = {}", n);
+            assert_close(&fft(&spectrum, true), &data, n);
+        }
+        // A unit impulse has the flat spectrum 1/sqrt(N)
+        let mut impulse = vec![Complex::new(0.0, 0.0); 12];
+        impulse[0] = Complex::new(1.0, 0.0);
+        for c in fft(&impulse, false) {
+            assert!((c - Complex::new(1.0 / 12f64.sqrt(), 0.0)).norm() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn real_input_matches_complex_input() {
+        for n in [2, 3, 12, 97, 128] {
+            let data: Vec<f64> = test_signal(n).iter().map(|c| c.re).collect();
+            let widened: Vec<Complex<f64>> = data.iter().map(|&x| Complex::new(x, 0.0)).collect();
+            let real = Fourier::from_real(data).unwrap();
+            let complex = Fourier::from_complex(widened).unwrap();
+
+            // Only bins 0..=N/2 are stored; the others follow from Hermitian symmetry
+            assert_eq!(real.transform.len(), n / 2 + 1, "N = {}", n);
+            let real_bins: Vec<Complex<f64>> = (0..n).map(|k| real.coefficient(k)).collect();
+            let complex_bins: Vec<Complex<f64>> = (0..n).map(|k| complex.coefficient(k)).collect();
+            assert_close(&real_bins, &complex_bins, n);
+
+            let k_max = real.max_frequency();
+            assert_close(&real.filtered_range(0, k_max).unwrap(), &complex.filtered_range(0, k_max).unwrap(), n);
+            let (_, real_power) = real.power_spectrum(false);
+            let (_, complex_power) = complex.power_spectrum(false);
+            for (a, e) in real_power.iter().zip(complex_power.iter()) {
+                assert!((a - e).abs() < 1e-9, "N = {}: {} != {}", n, a, e);
+            }
+        }
+    }
+
+    #[test]
+    fn period_is_estimated_from_the_autocorrelation() {
+        // A sinusoid with a fractional period, offset so that the mean must be removed
+        let sine: Vec<f64> = (0..500).map(|i| 2.0 + (std::f64::consts::TAU * i as f64 / 25.3).sin()).collect();
+        let period = estimate_period(&sine, 0.3).unwrap().unwrap();
+        assert!((period - 25.3).abs() < 0.1, "{}", period);
+
+        // The fundamental period wins over its multiples, and harmonics do not shorten it
+        let square: Vec<f32> = (0..400).map(|i| if i % 40 < 20 { 1.0 } else { -1.0 }).collect();
+        let period = estimate_period(&square, 0.3).unwrap().unwrap();
+        assert!((period - 40.0).abs() < 0.5, "{}", period);
+
+        // Signals without a repeating pattern
+        let step: Vec<f64> = (0..500).map(|i| if (150..350).contains(&i) { 1.0 } else { 0.0 }).collect();
+        assert_eq!(estimate_period(&step, 0.3).unwrap(), None);
+        assert_eq!(estimate_period(&[0.7; 64], 0.3).unwrap(), None);
+        assert_eq!(estimate_period(&[1.0, -1.0, 1.0], 0.3).unwrap(), None);
+        // Fewer than two periods
+        let short: Vec<f64> = (0..30).map(|i| (std::f64::consts::TAU * i as f64 / 20.0).sin()).collect();
+        assert_eq!(estimate_period(&short, 0.3).unwrap(), None);
+        assert!(estimate_period::<f64>(&[], 0.3).is_err());
+    }
+
+    #[test]
+    fn max_frequency_of_short_signals() {
+        for (n, expected) in [(1, 0), (2, 0), (3, 0), (4, 1), (5, 1)] {
+            let data: Vec<f64> = test_signal(n).iter().map(|c| c.re).collect();
+            let fourier = Fourier::from_real(data.clone()).unwrap();
+            assert_eq!(fourier.max_frequency(), expected, "N = {}", n);
+            // The DC component alone of a single sample is the sample itself
+            if n == 1 {
+                assert!((fourier.filtered_range(0, 0).unwrap()[0].re - data[0]).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn spectral_masks_have_expected_gains() {
+        let half_power = 0.5f64.sqrt();
+        assert_eq!(SpectralMask::Range(2, 5).gain(2.0), 1.0);
+        assert_eq!(SpectralMask::Range(2, 5).gain(5.5), 0.0);
+        let cosine = SpectralMask::RaisedCosine(2.0, 5.0, 2.0);
+        assert_eq!(cosine.gain(4.0), 1.0);
+        assert!((cosine.gain(6.0) - 0.5).abs() < 1e-12);
+        assert_eq!(cosine.gain(7.0), 0.0);
+        let butterworth = SpectralMask::Butterworth(2.0, 8.0, 3);
+        assert!((butterworth.gain(2.0) - half_power).abs() < 1e-3);
+        assert!((butterworth.gain(8.0) - half_power).abs() < 1e-3);
+        assert!((SpectralMask::Butterworth(0.0, 8.0, 3).gain(8.0) - half_power).abs() < 1e-12);
+        for k in [0.0, 3.0, 4.5, 10.0] {
+            let sum = SpectralMask::Gaussian(4.0, 1.5).gain(k) + SpectralMask::Notch(4.0, 1.5).gain(k);
+            assert!((sum - 1.0).abs() < 1e-12);
+        }
+        assert!(SpectralMask::Range(5, 2).validate().is_err());
+        assert!(SpectralMask::RaisedCosine(2.0, 5.0, -1.0).validate().is_err());
+        assert!(SpectralMask::Butterworth(0.0, 8.0, 0).validate().is_err());
+        assert!(SpectralMask::Gaussian(4.0, 0.0).validate().is_err());
+    }
+
+    #[test]
+    fn masks_filter_like_their_gain() {
+        let data: Vec<f64> = (0..64).map(|j| (2.0 * std::f64::consts::PI * 3.0 * j as f64 / 64.0).sin()
+            + (2.0 * std::f64::consts::PI * 12.0 * j as f64 / 64.0).sin()).collect();
+        let fourier = Fourier::from_real(data.clone()).unwrap();
+        let low: Vec<f64> = (0..64).map(|j| (2.0 * std::f64::consts::PI * 3.0 * j as f64 / 64.0).sin()).collect();
+
+        // A sharp range is filtered_range, and a smooth mask passing bin 3 and stopping bin 12 keeps the same tone
+        let range = fourier.filtered_range(0, 5).unwrap();
+        assert_eq!(range, fourier.filtered_with_mask(&SpectralMask::Range(0, 5)).unwrap());
+        let smooth = fourier.filtered_with_mask(&SpectralMask::RaisedCosine(0.0, 5.0, 4.0)).unwrap();
+        for ((r, s), e) in range.iter().zip(smooth.iter()).zip(low.iter()) {
+            assert!((r.re - e).abs() < 1e-9 && (s.re - e).abs() < 1e-9);
+        }
+        // Unit gain everywhere gives back the signal
+        for (a, e) in fourier.filtered_with_gain(|_| 1.0).iter().zip(data.iter()) {
+            assert!((a.re - e).abs() < 1e-9 && a.im == 0.0);
+        }
+    }
+
+    #[test]
+    fn resample_round_trips_band_limited_signals() {
+        let tone = |n: usize, j: usize| (2.0 * std::f64::consts::PI * 3.0 * j as f64 / n as f64).cos()
+            + 0.5 * (2.0 * std::f64::consts::PI * 5.0 * j as f64 / n as f64).sin();
+        let data: Vec<f64> = (0..32).map(|j| tone(32, j)).collect();
+        let fourier = Fourier::from_real(data.clone()).unwrap();
+
+        // Upsampling evaluates the band-limited signal at the new instants
+        for m in [33, 80, 128] {
+            let upsampled = fourier.resample(m).unwrap();
+            for (j, value) in upsampled.iter().enumerate() {
+                assert!((value.re - tone(m, j)).abs() < 1e-9 && value.im == 0.0, "M = {}, sample {}", m, j);
+            }
+            let back = Fourier::from_complex(upsampled).unwrap().resample(32).unwrap();
+            for (a, e) in back.iter().zip(data.iter()) {
+                assert!((a.re - e).abs() < 1e-9, "M = {}", m);
+            }
+        }
+        // Downsampling keeps the tones below the new Nyquist frequency
+        let downsampled = fourier.resample(16).unwrap();
+        for (j, value) in downsampled.iter().enumerate() {
+            assert!((value.re - tone(16, j)).abs() < 1e-9);
+        }
+        assert!(fourier.resample(0).is_err());
+    }
+
+    /// Asserts that two windows have the same coefficients.
+    fn assert_same_window(actual: Window, expected: Window, n: usize) {
+        let (a, e) = (actual.coefficients::<f64>(n), expected.coefficients::<f64>(n));
+        for (i, (a, e)) in a.iter().zip(e.iter()).enumerate() {
+            assert!((a - e).abs() < 1e-12, "{:?} vs {:?}, N = {}, sample {}: {} != {}", actual, expected, n, i, a, e);
+        }
+    }
+
+    #[test]
+    fn windows_are_periodic() {
+        let windows = [Window::Rectangular, Window::Hann, Window::Hamming, Window::Blackman,
+                       Window::Kaiser(6.0), Window::FlatTop, Window::Tukey(0.5)];
+        for window in windows {
+            for n in [16, 17] {
+                // DFT-even: w[i] = w[N - i], so only the first sample is unpaired
+                let w = window.coefficients::<f64>(n);
+                for i in 1..n {
+                    assert!((w[i] - w[n - i]).abs() < 1e-12, "{:?}, N = {}, sample {}", window, n, i);
+                }
+            }
+        }
+        // The first sample is the (zero) start of the period, and the peak falls on N/2
+        let hann = Window::Hann.coefficients::<f64>(16);
+        assert!(hann[0].abs() < 1e-12 && (hann[8] - 1.0).abs() < 1e-12);
+        assert!(hann[15] > 0.0);
+        assert!((Window::Hamming.coefficients::<f64>(16)[0] - 0.08).abs() < 1e-12);
+    }
+
+    #[test]
+    fn window_gains_match_known_values() {
+        for n in [16, 64, 101] {
+            assert!((Window::Hann.enbw(n) - 1.5).abs() < 1e-12, "N = {}", n);
+            assert!((Window::Hann.coherent_gain(n) - 0.5).abs() < 1e-12, "N = {}", n);
+            assert!((Window::Rectangular.enbw(n) - 1.0).abs() < 1e-12, "N = {}", n);
+            assert!((Window::Rectangular.coherent_gain(n) - 1.0).abs() < 1e-12, "N = {}", n);
+        }
+    }
+
+    #[test]
+    fn parameterized_windows_reduce_to_their_limits() {
+        for n in [16, 17] {
+            assert_same_window(Window::Kaiser(0.0), Window::Rectangular, n);
+            assert_same_window(Window::Tukey(0.0), Window::Rectangular, n);
+            assert_same_window(Window::Tukey(1.0), Window::Hann, n);
+        }
+        assert!(Window::Kaiser(-1.0).validate().is_err());
+        assert!(Window::Tukey(1.5).validate().is_err());
+        assert!(Window::Tukey(f32::NAN).validate().is_err());
+    }
+
+    #[test]
+    fn large_prime_factors_use_the_chirp_z_transform() {
+        // 67 and 2 * 67 exceed BLUESTEIN_THRESHOLD, so both go through Bluestein's algorithm
+        for n in [67, 134] {
+            assert!(largest_factor(n) > BLUESTEIN_THRESHOLD);
+            let data = test_signal(n);
+            assert_close(&fft(&data, false), &naive_dft(&data, false), n);
+            assert_close(&fft(&data, true), &naive_dft(&data, true), n);
+            let step = 2.0 * std::f64::consts::PI / n as f64;
+            assert_close(&chirp_z(&data, n, 0.0, step).unwrap(), &naive_dft(&data, false), n);
+        }
+    }
+
+    #[test]
+    fn zoom_over_the_full_band_matches_the_fft() {
+        for n in [12, 67, 128] {
+            let data = test_signal(n);
+            // N points over [0, N - 1] land exactly on the DFT bins
+            let zoom = zoom_fft(&data, 0.0, (n - 1) as f64, n).unwrap();
+            assert_close(&zoom, &fft(&data, false), n);
+
+            let real: Vec<f64> = data.iter().map(|c| c.re).collect();
+            let fourier = Fourier::from_real(real).unwrap();
+            let (freq, power) = fourier.power_spectrum(false);
+            let (zoom_freq, zoom_power) = fourier.zoom_spectrum(0.0, (n - 1) as f64, n).unwrap();
+            for k in 0..n {
+                assert!((zoom_freq[k] - freq[k]).abs() < 1e-9, "N = {}, bin {}", n, k);
+                assert!((zoom_power[k] - power[k]).abs() < 1e-9, "N = {}, bin {}", n, k);
+            }
+        }
+        let data = test_signal(12);
+        assert!(zoom_fft(&data, 3.0, 3.0, 8).is_err());
+        assert!(zoom_fft(&data, 0.0, 3.0, 1).is_err());
+        assert!(chirp_z::<f64>(&[], 4, 0.0, 0.1).is_err());
+    }
+
+    #[test]
+    fn stft_inverts_with_overlapping_windows() {
+        for n in [100, 101] {
+            let data: Vec<f64> = test_signal(n).iter().map(|c| c.re).collect();
+            // Hann at 50% and 75% overlap, and non-overlapping rectangular frames
+            for (frame, hop, window) in [(16, 8, Window::Hann), (16, 4, Window::Hann), (10, 10, Window::Rectangular)] {
+                let stft = Stft::from_real(&data, frame, hop, window).unwrap();
+                let restored = stft.inverse();
+                assert_eq!(restored.len(), n);
+                for (i, (a, e)) in restored.iter().zip(data.iter()).enumerate() {
+                    assert!((a - e).abs() < 1e-9, "N = {}, L = {}, hop = {}, sample {}: {} != {}", n, frame, hop, i, a, e);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn stft_frames_cover_the_signal() {
+        // Frames are centered every hop samples until one reaches past the end of the signal
+        for (n, frame, hop, expected) in [(100, 16, 8, 13), (101, 16, 8, 13), (100, 16, 4, 24), (5, 16, 4, 1)] {
+            let data: Vec<f64> = test_signal(n).iter().map(|c| c.re).collect();
+            let stft = Stft::from_real(&data, frame, hop, Window::Hann).unwrap();
+            assert_eq!(stft.num_frames(), expected, "N = {}, L = {}, hop = {}", n, frame, hop);
+            assert_eq!(stft.num_bins(), frame / 2 + 1);
+            assert_eq!(stft.frame(0).len(), stft.num_bins());
+            assert_eq!(stft.power_spectrogram().len(), stft.num_frames() * stft.num_bins());
+            assert_eq!(stft.frame_times().last().copied(), Some(((expected - 1) * hop) as f64));
+            assert_eq!(stft.bin_frequencies(), (0..=frame / 2).map(|k| k as f64).collect::<Vec<_>>());
+        }
+
+        // A tone at 4 cycles per frame peaks at bin 4 in every frame
+        let tone: Vec<f64> = (0..128).map(|i| (std::f64::consts::TAU * 4.0 * i as f64 / 32.0).cos()).collect();
+        let stft = Stft::from_real(&tone, 32, 16, Window::Hann).unwrap();
+        let power = stft.power_spectrogram();
+        for (i, frame) in power.chunks(stft.num_bins()).enumerate().skip(1).take(stft.num_frames() - 2) {
+            let peak = (0..frame.len()).max_by(|&a, &b| frame[a].total_cmp(&frame[b])).unwrap();
+            assert_eq!(peak, 4, "frame {}", i);
+        }
+
+        let data = [1.0f64; 10];
+        assert!(Stft::from_real(&data, 8, 0, Window::Hann).is_err());
+        assert!(Stft::from_real(&data, 8, 9, Window::Hann).is_err());
+        assert!(Stft::from_real(&data, 1, 1, Window::Hann).is_err());
+    }
+
+    /// Integrates a power spectral density over its frequency bins.
+    fn integrate_density(freq: &[f64], density: &[f64]) -> f64 {
+        density.iter().sum::<f64>() * (freq[1] - freq[0])
+    }
+
+    #[test]
+    fn density_integrates_to_the_signal_power() {
+        const SAMPLE_RATE: f64 = 1000.0;
+        // 2 sin at 50 Hz (10 cycles per segment), plus DC 0.5 and 0.3 at Nyquist: power 2 + 0.25 + 0.09
+        let data: Vec<f64> = (0..2000).map(|i| {
+            let sign = if i % 2 == 0 { 1.0 } else { -1.0 };
+            2.0 * (std::f64::consts::TAU * 50.0 * i as f64 / SAMPLE_RATE).sin() + 0.5 + 0.3 * sign
+        }).collect();
+        for window in [Window::Rectangular, Window::Hann, Window::Blackman] {
+            for sides in [SpectrumSides::OneSided, SpectrumSides::TwoSided] {
+                let (freq, density) = welch(&data, SAMPLE_RATE, 200, 100, window, sides).unwrap();
+                let power = integrate_density(&freq, &density);
+                assert!((power - 2.34).abs() < 1e-9, "{:?}, {:?}: {}", window, sides, power);
+            }
+        }
+
+        // One-sided bins hold the power of both signs, except DC and Nyquist which have no mirror
+        let (freq, density) = bartlett(&data, SAMPLE_RATE, 200, SpectrumSides::OneSided).unwrap();
+        let bin_width = freq[1] - freq[0];
+        assert_eq!(freq.len(), 101);
+        assert!((density[0] * bin_width - 0.25).abs() < 1e-9);
+        assert!((density[10] * bin_width - 2.0).abs() < 1e-9);
+        assert!((density[100] * bin_width - 0.09).abs() < 1e-9);
+        let (freq, density) = bartlett(&data, SAMPLE_RATE, 200, SpectrumSides::TwoSided).unwrap();
+        assert_eq!((freq[0], freq.len()), (-500.0, 200));
+        assert!((density[90] * bin_width - 1.0).abs() < 1e-9 && (density[110] * bin_width - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn white_noise_density_is_flat_at_its_variance() {
+        const SAMPLE_RATE: f64 = 100.0;
+        let noise: Vec<f64> = crate::noise::NoiseGenerator::new(7).generate(crate::noise::NoiseKind::White, 8192);
+        // Non-overlapping rectangular segments that cover the signal keep its power exactly
+        let (freq, density) = bartlett(&noise, SAMPLE_RATE, 256, SpectrumSides::OneSided).unwrap();
+        assert!((integrate_density(&freq, &density) - 1.0).abs() < 1e-9);
+        // Overlapping windowed segments weight samples unevenly, so the power is only close
+        let (freq, density) = welch(&noise, SAMPLE_RATE, 256, 128, Window::Hann, SpectrumSides::OneSided).unwrap();
+        assert!((integrate_density(&freq, &density) - 1.0).abs() < 0.05);
+        // Unit variance spread over fs/2 Hz
+        let interior = &density[1..density.len() - 1];
+        let level = interior.iter().sum::<f64>() / interior.len() as f64;
+        assert!((level - 2.0 / SAMPLE_RATE).abs() < 0.1 * 2.0 / SAMPLE_RATE, "{}", level);
+
+        assert!(welch(&noise, SAMPLE_RATE, 256, 256, Window::Hann, SpectrumSides::OneSided).is_err());
+        assert!(welch(&noise[..100], SAMPLE_RATE, 256, 0, Window::Hann, SpectrumSides::OneSided).is_err());
+        assert!(bartlett(&noise, 0.0, 256, SpectrumSides::OneSided).is_err());
+    }
+
+    /// N samples of a sum of cosines, given as (cycles per N samples, amplitude), with a common phase offset.
+    fn tones(n: usize, components: &[(f64, f64)]) -> Vec<f64> {
+        (0..n).map(|j| components.iter()
+            .map(|&(frequency, amplitude)| amplitude * (std::f64::consts::TAU * frequency * j as f64 / n as f64 + 0.4).cos())
+            .sum()).collect()
+    }
+
+    #[test]
+    fn peak_interpolation_recovers_off_bin_frequencies() {
+        let data = tones(64, &[(10.3, 1.0)]);
+        let on_bin = Fourier::from_real(tones(64, &[(10.0, 1.0)])).unwrap();
+        let reference = on_bin.spectral_peaks(1, 0.0, PeakInterpolation::None)[0].power;
+        for (window, interpolation, tolerance) in [
+            (Window::Rectangular, PeakInterpolation::Quinn, 0.02),
+            // A parabola through the magnitudes is biased, but still much closer than the 0.3-bin error of the peak bin
+            (Window::Hann, PeakInterpolation::Parabolic, 0.1),
+            (Window::Hann, PeakInterpolation::Gaussian, 0.02),
+            (Window::Blackman, PeakInterpolation::Gaussian, 0.02),
+        ] {
+            let fourier = Fourier::from_real_windowed(data.clone(), window).unwrap();
+            let peaks = fourier.spectral_peaks(1, 0.0, interpolation);
+            assert_eq!(peaks.len(), 1);
+            let error = (peaks[0].frequency - 10.3).abs();
+            assert!(error < tolerance, "{:?}, {:?}: {}", window, interpolation, peaks[0].frequency);
+            // The power at the refined frequency is that of the tone, not of the nearest bin
+            assert!((peaks[0].power / reference - 1.0).abs() < 0.05, "{:?}, {:?}: {}", window, interpolation, peaks[0].power / reference);
+        }
+        let fourier = Fourier::from_real(data).unwrap();
+        assert_eq!(fourier.spectral_peaks(1, 0.0, PeakInterpolation::None)[0].frequency, 10.0);
+    }
+
+    #[test]
+    fn peaks_are_filtered_by_prominence_and_count() {
+        // Bin-centered tones, and a weak off-bin one beside the 40-cycle tone
+        let data = tones(128, &[(5.0, 0.2), (20.0, 1.0), (40.0, 0.5), (41.5, 0.05)]);
+        let fourier = Fourier::from_real(data).unwrap();
+        let (freq, power) = fourier.power_spectrum(false);
+        let power_of = |k: f64| power[freq.iter().position(|&f| f == k).unwrap()];
+
+        let all = fourier.spectral_peaks(10, 0.0, PeakInterpolation::None);
+        let frequencies: Vec<f64> = all.iter().map(|peak| peak.frequency).collect();
+        assert_eq!(&frequencies[..3], &[20.0, 40.0, 5.0], "{:?}", frequencies);
+        assert!(all.iter().all(|peak| peak.frequency >= 0.0));
+        assert!(all.windows(2).all(|pair| pair[0].power >= pair[1].power));
+
+        // The threshold drops the weak tone next to the 40-cycle one, and anything below the 5-cycle tone
+        let strong = fourier.spectral_peaks(10, 0.5 * power_of(5.0), PeakInterpolation::None);
+        let frequencies: Vec<f64> = strong.iter().map(|peak| peak.frequency).collect();
+        assert_eq!(frequencies, vec![20.0, 40.0, 5.0]);
+        assert!(strong.iter().all(|peak| peak.prominence >= 0.5 * power_of(5.0)));
+        let strongest = fourier.spectral_peaks(2, 0.5 * power_of(5.0), PeakInterpolation::None);
+        assert_eq!(strongest.iter().map(|peak| peak.frequency).collect::<Vec<_>>(), vec![20.0, 40.0]);
+        assert!(fourier.spectral_peaks(10, 2.0 * power_of(20.0), PeakInterpolation::None).is_empty());
+
+        assert_eq!(find_peaks(&[0.0, 2.0, 1.0, 3.0, 0.0], 0.0), vec![(1, 1.0), (3, 3.0)]);
+        assert_eq!(find_peaks(&[0.0, 2.0, 1.0, 3.0, 0.0], 1.5), vec![(3, 3.0)]);
+    }
+
+    #[test]
+    fn pure_delays_have_constant_group_delay() {
+        // An impulse delayed by d samples has the linear phase -2 pi k d / N
+        for (n, d) in [(32, 5), (33, 7), (64, 0)] {
+            let mut impulse = vec![0.0f64; n];
+            impulse[d] = 1.0;
+            let fourier = Fourier::from_real(impulse).unwrap();
+            let (freq, delays) = fourier.group_delay(false);
+            for (k, &delay) in delays.iter().enumerate() {
+                assert!((delay - d as f64).abs() < 1e-9, "N = {}, d = {}, bin {}: {}", n, d, k, delay);
+            }
+            let (_, phases) = fourier.phase_spectrum(false, false);
+            for ((&f, &phase), k) in freq.iter().zip(phases.iter()).zip(0..) {
+                let expected = Complex::from_polar(1.0, -std::f64::consts::TAU * f * d as f64 / n as f64);
+                assert!((Complex::from_polar(1.0, phase) - expected).norm() < 1e-9, "N = {}, d = {}, bin {}", n, d, k);
+                assert!(phase > -std::f64::consts::PI - 1e-12 && phase <= std::f64::consts::PI + 1e-12);
+            }
+            // Unwrapping the shifted spectrum recovers the straight line through zero
+            let (freq, unwrapped) = fourier.phase_spectrum(true, true);
+            assert_eq!(freq[0], -((n / 2) as f64), "N = {}", n);
+            assert!(freq.windows(2).all(|pair| pair[1] - pair[0] == 1.0), "N = {}", n);
+            let zero = freq.iter().position(|&f| f == 0.0).unwrap();
+            for (&f, &phase) in freq.iter().zip(unwrapped.iter()) {
+                let expected = -std::f64::consts::TAU * f * d as f64 / n as f64 + unwrapped[zero];
+                assert!((phase - expected).abs() < 1e-9, "N = {}, d = {}, f = {}", n, d, f);
+            }
+        }
+    }
+
+    #[test]
+    fn phase_is_zero_where_the_transform_vanishes() {
+        // A delayed tone at 3 cycles leaves every other bin at rounding-noise level
+        let tone: Vec<f64> = (0..64).map(|j| (std::f64::consts::TAU * 3.0 * (j as f64 - 2.0) / 64.0).cos()).collect();
+        let fourier = Fourier::from_real(tone).unwrap();
+        let (freq, phases) = fourier.phase_spectrum(false, false);
+        let (_, delays) = fourier.group_delay(false);
+        for ((&f, &phase), &delay) in freq.iter().zip(phases.iter()).zip(delays.iter()) {
+            if f == 3.0 || f == 61.0 {
+                assert!(phase.abs() > 0.1, "bin {}: {}", f, phase);
+            } else {
+                assert_eq!((phase, delay), (0.0, 0.0), "bin {}", f);
+            }
+        }
+        let (_, unwrapped) = fourier.phase_spectrum(false, true);
+        assert!(unwrapped.iter().all(|phase| phase.abs() <= std::f64::consts::PI + 1e-12));
+    }
+
+    #[test]
+    fn hilbert_transform_of_cosine_is_sine() {
+        for n in [128, 127] {
+            // Whole cycles: the transform is exact everywhere
+            let phase = |j: usize, cycles: f64| std::f64::consts::TAU * cycles * j as f64 / n as f64 + 0.3;
+            let cosine: Vec<f64> = (0..n).map(|j| phase(j, 9.0).cos()).collect();
+            let analytic = hilbert(&cosine).unwrap();
+            for (j, z) in analytic.iter().enumerate() {
+                assert!((z.re - cosine[j]).abs() < 1e-9 && (z.im - phase(j, 9.0).sin()).abs() < 1e-9, "N = {}, sample {}", n, j);
+            }
+            // A fractional number of cycles leaks at the edges, but not in the middle of the signal
+            let cosine: Vec<f64> = (0..n).map(|j| phase(j, 9.3).cos()).collect();
+            let analytic = hilbert(&cosine).unwrap();
+            for (j, z) in analytic.iter().enumerate().take(3 * n / 4).skip(n / 4) {
+                assert!((z.im - phase(j, 9.3).sin()).abs() < 0.05, "N = {}, sample {}: {}", n, j, z.im);
+            }
+        }
+        assert!(hilbert::<f64>(&[]).is_err());
+    }
+
+    #[test]
+    fn envelope_follows_amplitude_modulation() {
+        for n in [256, 255] {
+            // Carrier at 40 cycles modulated at 3 cycles: all components stay below Nyquist
+            let modulation = |j: usize| 1.0 + 0.5 * (std::f64::consts::TAU * 3.0 * j as f64 / n as f64).cos();
+            let carrier = |j: usize| std::f64::consts::TAU * 40.0 * j as f64 / n as f64;
+            let signal: Vec<f64> = (0..n).map(|j| modulation(j) * carrier(j).cos()).collect();
+            let analytic = hilbert(&signal).unwrap();
+
+            for (j, value) in envelope(&analytic).iter().enumerate() {
+                assert!((value - modulation(j)).abs() < 1e-9, "N = {}, sample {}", n, j);
+            }
+            let phase = instantaneous_phase(&analytic);
+            for (j, value) in phase.iter().enumerate() {
+                assert!((value - phase[0] - carrier(j)).abs() < 1e-9, "N = {}, sample {}", n, j);
+            }
+            // 40 cycles per N samples at N Hz is 40 Hz
+            let frequency = instantaneous_frequency(&analytic, n as f64);
+            assert_eq!(frequency.len(), n - 1);
+            assert!(frequency.iter().all(|f| (f - 40.0).abs() < 1e-6), "N = {}", n);
+        }
+    }
+
+    const TRIG_KINDS: [TrigKind; 8] = [TrigKind::DctI, TrigKind::DctII, TrigKind::DctIII, TrigKind::DctIV,
+                                       TrigKind::DstI, TrigKind::DstII, TrigKind::DstIII, TrigKind::DstIV];
+
+    /// Orthonormal DCT/DST evaluated directly from the textbook definition of each type.
+    fn direct_trig(data: &[f64], kind: TrigKind) -> Vec<f64> {
+        let n = data.len();
+        let pi = std::f64::consts::PI;
+        let half = std::f64::consts::FRAC_1_SQRT_2;
+        // Scale 1/sqrt(2) of the first or last index, where the definition has one
+        let edge = |i: usize, first: bool, last: bool| if (first && i == 0) || (last && i == n - 1) { half } else { 1.0 };
+        (0..n)
+            .map(|k| {
+                let (scale, sum): (f64, f64) = match kind {
+                    TrigKind::DctI => ((2.0 / (n - 1) as f64).sqrt() * edge(k, true, true),
+                        (0..n).map(|j| edge(j, true, true) * data[j] * (pi * (j * k) as f64 / (n - 1) as f64).cos()).sum()),
+                    TrigKind::DctII => ((2.0 / n as f64).sqrt() * edge(k, true, false),
+                        (0..n).map(|j| data[j] * (pi * (j as f64 + 0.5) * k as f64 / n as f64).cos()).sum()),
+                    TrigKind::DctIII => ((2.0 / n as f64).sqrt(),
+                        (0..n).map(|j| edge(j, true, false) * data[j] * (pi * j as f64 * (k as f64 + 0.5) / n as f64).cos()).sum()),
+                    TrigKind::DctIV => ((2.0 / n as f64).sqrt(),
+                        (0..n).map(|j| data[j] * (pi * (j as f64 + 0.5) * (k as f64 + 0.5) / n as f64).cos()).sum()),
+                    TrigKind::DstI => ((2.0 / (n + 1) as f64).sqrt(),
+                        (0..n).map(|j| data[j] * (pi * ((j + 1) * (k + 1)) as f64 / (n + 1) as f64).sin()).sum()),
+                    TrigKind::DstII => ((2.0 / n as f64).sqrt() * edge(k, false, true),
+                        (0..n).map(|j| data[j] * (pi * (j as f64 + 0.5) * (k + 1) as f64 / n as f64).sin()).sum()),
+                    TrigKind::DstIII => ((2.0 / n as f64).sqrt(),
+                        (0..n).map(|j| edge(j, false, true) * data[j] * (pi * (j + 1) as f64 * (k as f64 + 0.5) / n as f64).sin()).sum()),
+                    TrigKind::DstIV => ((2.0 / n as f64).sqrt(),
+                        (0..n).map(|j| data[j] * (pi * (j as f64 + 0.5) * (k as f64 + 0.5) / n as f64).sin()).sum()),
+                };
+                scale * sum
+            })
+            .collect()
+    }
+
+    #[test]
+    fn trig_transforms_match_their_definitions() {
+        for kind in TRIG_KINDS {
+            for n in [2, 3, 8, 13, 67] {
+                let data: Vec<f64> = test_signal(n).iter().map(|c| c.re).collect();
+                let coefficients = trig_transform(&data, kind).unwrap();
+                for (k, (a, e)) in coefficients.iter().zip(direct_trig(&data, kind).iter()).enumerate() {
+                    assert!((a - e).abs() < 1e-9, "{:?}, N = {}, coefficient {}: {} != {}", kind, n, k, a, e);
+                }
+            }
+        }
+        assert!(trig_transform(&[1.0f64], TrigKind::DctI).is_err());
+        assert!(trig_transform::<f64>(&[], TrigKind::DctII).is_err());
+    }
+
+    #[test]
+    fn trig_transforms_invert_and_keep_energy() {
+        for kind in TRIG_KINDS {
+            for n in [2, 3, 8, 13, 67] {
+                let data: Vec<f64> = test_signal(n).iter().map(|c| c.re).collect();
+                let transform = TrigTransform::from_real(data.clone(), kind).unwrap();
+                let energy: f64 = data.iter().map(|x| x * x).sum();
+                let coefficient_energy: f64 = transform.coefficients().iter().map(|c| c * c).sum();
+                assert!((energy - coefficient_energy).abs() < 1e-9 * energy, "{:?}, N = {}", kind, n);
+
+                let restored = trig_transform(transform.coefficients(), kind.inverse()).unwrap();
+                let unfiltered = transform.filtered_range(0, transform.max_frequency()).unwrap();
+                for (j, ((a, b), e)) in restored.iter().zip(unfiltered.iter()).zip(data.iter()).enumerate() {
+                    assert!((a - e).abs() < 1e-9 && (b - e).abs() < 1e-9, "{:?}, N = {}, sample {}", kind, n, j);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn signal_quality_of_a_distorted_noisy_sine() {
+        // Fundamental of power 0.5 at bin 50, harmonics of power 0.005 + 0.00125 and white noise of power 1e-4
+        let n = 1024;
+        let noise: Vec<f64> = crate::noise::NoiseGenerator::new(3).generate(crate::noise::NoiseKind::White, n);
+        let data: Vec<f64> = (0..n).map(|j| {
+            let phase = std::f64::consts::TAU * 50.0 * j as f64 / n as f64;
+            phase.sin() + 0.1 * (2.0 * phase).sin() + 0.05 * (3.0 * phase + 1.0).cos() + 0.01 * noise[j]
+        }).collect();
+        let (harmonics, noise_power) = (0.005 + 0.00125, 1e-4);
+        let expected_sinad = 10.0 * (0.5 / (harmonics + noise_power)).log10();
+
+        let quality = Fourier::from_real(data.clone()).unwrap().signal_quality(5).unwrap();
+        assert!((quality.fundamental - 50.0).abs() < 0.01, "{:?}", quality);
+        assert!((quality.thd - 10.0 * (harmonics / 0.5).log10()).abs() < 0.05, "{:?}", quality);
+        // A few noise bins fall within the main lobes and count as signal or harmonics
+        assert!((quality.snr - 10.0 * (0.5 / noise_power).log10()).abs() < 0.3, "{:?}", quality);
+        assert!((quality.sinad - expected_sinad).abs() < 0.1, "{:?}", quality);
+        assert!((quality.enob - (quality.sinad - 1.76) / 6.02).abs() < 1e-12);
+
+        // Off-bin, a low-leakage window keeps the measurements
+        let shifted: Vec<f64> = (0..n).map(|j| {
+            let phase = std::f64::consts::TAU * 50.3 * j as f64 / n as f64;
+            phase.sin() + 0.1 * (2.0 * phase).sin() + 0.05 * (3.0 * phase + 1.0).cos() + 0.01 * noise[j]
+        }).collect();
+        let quality = Fourier::from_real_windowed(shifted, Window::Blackman).unwrap().signal_quality(5).unwrap();
+        assert!((quality.fundamental - 50.3).abs() < 0.05, "{:?}", quality);
+        assert!((quality.thd - 10.0 * (harmonics / 0.5).log10()).abs() < 0.2, "{:?}", quality);
+        assert!((quality.sinad - expected_sinad).abs() < 0.2, "{:?}", quality);
+    }
+
+    #[test]
+    fn signal_quality_main_lobes_are_clipped_to_the_band() {
+        // A tone next to Nyquist: its lobe reaches past N/2, and its harmonics fold back
+        let tone: Vec<f64> = (0..64).map(|j| (std::f64::consts::TAU * 31.0 * j as f64 / 64.0).cos()).collect();
+        let quality = Fourier::from_real(tone).unwrap().signal_quality(3).unwrap();
+        assert!((quality.fundamental - 31.0).abs() < 1e-9, "{:?}", quality);
+
+        // Lobes wider than the whole band leave no room for a fundamental away from DC
+        let short: Vec<f64> = (0..8).map(|j| (std::f64::consts::TAU * 2.0 * j as f64 / 8.0).sin()).collect();
+        assert!(Fourier::from_real_windowed(short.clone(), Window::FlatTop).unwrap().signal_quality(3).is_err());
+        // Padding widens the lobes in bins of the padded transform as well
+        let padded = Fourier::from_real_windowed_padded(short, Window::Kaiser(30.0), Padding::ToLength(32)).unwrap();
+        assert!(padded.signal_quality(3).is_err());
+
+        let complex = Fourier::from_complex(test_signal(16)).unwrap();
+        assert!(complex.signal_quality(3).is_err());
+    }
+
+    #[test]
+    fn frequencies_follow_the_sample_rate_and_unit() {
+        let data: Vec<f64> = test_signal(8).iter().map(|c| c.re).collect();
+        let fourier = Fourier::from_real(data.clone()).unwrap().with_sample_rate(1000.0).unwrap();
+        assert_eq!(fourier.sample_rate(), Some(1000.0));
+        assert_eq!(fourier.frequency_resolution(), Some(125.0));
+
+        let expected_hz = [0.0, 125.0, 250.0, 375.0, 500.0, 625.0, 750.0, 875.0];
+        for (unit, scale) in [(FrequencyUnit::Bin, 1.0 / 125.0), (FrequencyUnit::Hertz, 1.0), (FrequencyUnit::RadiansPerSecond, std::f64::consts::TAU)] {
+            let (freq, power) = fourier.power_spectrum_in(false, unit).unwrap();
+            assert_eq!(power, fourier.power_spectrum(false).1);
+            for (f, e) in freq.iter().zip(expected_hz.iter()) {
+                assert!((f - e * scale).abs() < 1e-9, "{:?}: {} != {}", unit, f, e * scale);
+            }
+        }
+        let (shifted, _) = fourier.power_spectrum_in(true, FrequencyUnit::Hertz).unwrap();
+        assert_eq!(shifted, vec![-500.0, -375.0, -250.0, -125.0, 0.0, 125.0, 250.0, 375.0]);
+
+        // Padding refines the bins but keeps them in Hz; a sample spacing is the inverse rate
+        let padded = Fourier::from_real_padded(data.clone(), Padding::ToLength(16)).unwrap().with_sample_spacing(1e-3).unwrap();
+        assert_eq!(padded.frequency_resolution(), Some(62.5));
+        assert_eq!(padded.power_spectrum_in(false, FrequencyUnit::Hertz).unwrap().0[3], 187.5);
+
+        // Filtering in Hz keeps the bins inside the band
+        let tones: Vec<f64> = (0..8).map(|j| (std::f64::consts::TAU * j as f64 / 8.0).cos() + (std::f64::consts::TAU * 3.0 * j as f64 / 8.0).cos()).collect();
+        let fourier = Fourier::from_real(tones).unwrap().with_sample_rate(1000.0).unwrap();
+        for (j, value) in fourier.filtered_hz(100.0, 200.0).unwrap().iter().enumerate() {
+            assert!((value.re - (std::f64::consts::TAU * j as f64 / 8.0).cos()).abs() < 1e-9, "sample {}", j);
+        }
+        assert!(fourier.filtered_hz(130.0, 200.0).unwrap().iter().all(|value| value.norm() < 1e-12));
+    }
+
+    #[test]
+    fn invalid_sample_rates_are_rejected() {
+        let data: Vec<f64> = test_signal(8).iter().map(|c| c.re).collect();
+        let fourier = || Fourier::from_real(data.clone()).unwrap();
+        for rate in [0.0, -1000.0, f64::NAN, f64::INFINITY] {
+            assert!(fourier().with_sample_rate(rate).is_err(), "{}", rate);
+            assert!(fourier().with_sample_spacing(rate).is_err(), "{}", rate);
+        }
+
+        // Physical units need a sample rate; bins do not
+        let plain = fourier();
+        assert_eq!(plain.sample_rate(), None);
+        assert_eq!(plain.frequency_resolution(), None);
+        assert!(plain.power_spectrum_in(false, FrequencyUnit::Bin).is_ok());
+        assert!(plain.power_spectrum_in(false, FrequencyUnit::Hertz).is_err());
+        assert!(plain.power_spectrum_in(false, FrequencyUnit::RadiansPerSecond).is_err());
+        assert!(plain.filtered_hz(0.0, 100.0).is_err());
+
+        let rated = fourier().with_sample_rate(1000.0).unwrap();
+        assert!(rated.filtered_hz(0.0, 600.0).is_err());
+        assert!(rated.filtered_hz(-1.0, 100.0).is_err());
+        assert!(rated.filtered_hz(200.0, 100.0).is_err());
+    }
+
+    /// Full linear convolution evaluated directly from its definition.
+    fn direct_convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+        let mut result = vec![0.0; a.len() + b.len() - 1];
+        for (i, &x) in a.iter().enumerate() {
+            for (j, &y) in b.iter().enumerate() {
+                result[i + j] += x * y;
+            }
+        }
+        result
+    }
+
+    fn assert_close_real(actual: &[f64], expected: &[f64], context: &str) {
+        assert_eq!(actual.len(), expected.len(), "{}", context);
+        for (i, (a, e)) in actual.iter().zip(expected.iter()).enumerate() {
+            assert!((a - e).abs() < 1e-9, "{}, sample {}: {} != {}", context, i, a, e);
+        }
+    }
+
+    #[test]
+    fn convolution_matches_direct_evaluation() {
+        for (n, m) in [(1, 1), (10, 3), (3, 10), (17, 17), (50, 8)] {
+            let a: Vec<f64> = test_signal(n).iter().map(|c| c.re).collect();
+            let b: Vec<f64> = test_signal(m).iter().map(|c| c.im).collect();
+            let context = format!("N = {}, M = {}", n, m);
+            let full = direct_convolve(&a, &b);
+            assert_close_real(&convolve(&a, &b, ConvolutionMode::Full).unwrap(), &full, &context);
+            assert_close_real(&convolve(&a, &b, ConvolutionMode::Same).unwrap(), &full[(m - 1) / 2..(m - 1) / 2 + n], &context);
+            let valid_start = n.min(m) - 1;
+            assert_close_real(&convolve(&a, &b, ConvolutionMode::Valid).unwrap(), &full[valid_start..valid_start + n.max(m) - n.min(m) + 1], &context);
+
+            // Circular convolution wraps the full result around max(N, M) samples
+            let period = n.max(m);
+            let mut circular = vec![0.0; period];
+            for (i, &value) in full.iter().enumerate() {
+                circular[i % period] += value;
+            }
+            assert_close_real(&convolve(&a, &b, ConvolutionMode::Circular).unwrap(), &circular, &context);
+
+            // r[k] = sum_n a[n + k] b[n] for the lags -(M - 1)..=N - 1
+            let lags = -(m as isize - 1)..n as isize;
+            let correlation: Vec<f64> = lags.map(|k| (0..m as isize)
+                .filter(|&j| (0..n as isize).contains(&(j + k)))
+                .map(|j| a[(j + k) as usize] * b[j as usize])
+                .sum()).collect();
+            assert_close_real(&correlate(&a, &b, ConvolutionMode::Full).unwrap(), &correlation, &context);
+        }
+        assert!(convolve::<f64>(&[], &[1.0], ConvolutionMode::Full).is_err());
+        assert!(correlate(&[1.0], &[f64::NAN], ConvolutionMode::Full).is_err());
+    }
+
+    #[test]
+    fn overlap_add_matches_direct_convolution() {
+        let input: Vec<f64> = test_signal(48).iter().map(|c| c.re).collect();
+        let kernel: Vec<f64> = test_signal(7).iter().map(|c| c.im).collect();
+        let expected = direct_convolve(&input, &kernel);
+        // Block lengths dividing 48 or not, and one longer than the whole input
+        for block_length in [1, 4, 5, 16, 48, 100] {
+            let mut convolver = OverlapAdd::new(&kernel, block_length).unwrap();
+            let mut output = convolver.process(&input);
+            assert_eq!(output.len(), input.len());
+            output.extend(convolver.finish());
+            assert_close_real(&output, &expected, &format!("block length {}", block_length));
+
+            // Chunks of the stream need not line up with the blocks, and finish resets it
+            let mut output = Vec::new();
+            for chunk in [&input[..7], &input[7..20], &input[20..]] {
+                output.extend(convolver.process(chunk));
+            }
+            output.extend(convolver.finish());
+            assert_close_real(&output, &expected, &format!("chunked, block length {}", block_length));
+        }
+        assert!(OverlapAdd::new(&kernel, 0).is_err());
+        assert!(OverlapAdd::<f64>::new(&[], 4).is_err());
     }
-    result
 }
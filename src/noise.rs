@@ -0,0 +1,180 @@
+#![allow(dead_code)]
+
+/// Seeded pseudo-random noise generators for adding noise to test signals.
+///
+/// The same seed always gives the same sequence, so a noisy example can be reproduced exactly.
+/// All noise is returned with zero mean and unit RMS, to be scaled by the caller.
+use crate::math::{self, cast, to_f64, Complex, Real};
+
+/// Spectral shape of the generated noise.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NoiseKind {
+    /// Gaussian samples, flat power spectrum.
+    White,
+    /// Uniformly distributed samples, flat power spectrum.
+    Uniform,
+    /// Power falling as 1/f (-3 dB per octave), obtained by shaping white noise in the frequency domain.
+    Pink,
+    /// Power falling as 1/f² (-6 dB per octave), the running sum of white noise (a random walk).
+    Brown,
+}
+
+/// SplitMix64 pseudo-random number generator.
+pub struct NoiseGenerator {
+    state: u64,
+}
+
+impl NoiseGenerator {
+    /// Creates a generator from a seed.
+    pub fn new(seed: u64) -> Self {
+        NoiseGenerator { state: seed }
+    }
+
+    /// Returns the next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a uniform sample in [0, 1).
+    pub fn next_uniform(&mut self) -> f64 {
+        // The top 53 bits fill the mantissa of a double exactly
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns a standard normal sample (Box-Muller transform).
+    pub fn next_gaussian(&mut self) -> f64 {
+        let radius = (-2.0 * (1.0 - self.next_uniform()).ln()).sqrt();
+        radius * (2.0 * std::f64::consts::PI * self.next_uniform()).cos()
+    }
+
+    /// Generates `length` samples of noise of the given kind, with zero mean and unit RMS.
+    pub fn generate<T: Real>(&mut self, kind: NoiseKind, length: usize) -> Vec<T> {
+        let samples: Vec<f64> = match kind {
+            NoiseKind::White => (0..length).map(|_| self.next_gaussian()).collect(),
+            NoiseKind::Uniform => (0..length).map(|_| self.next_uniform() - 0.5).collect(),
+            NoiseKind::Pink => {
+                // Amplitudes scaled by 1/sqrt(f); the DC bin is dropped
+                let white: Vec<Complex<f64>> = (0..length).map(|_| Complex::new(self.next_gaussian(), 0.0)).collect();
+                let mut spectrum = math::fft(&white, false);
+                for (k, value) in spectrum.iter_mut().enumerate() {
+                    let frequency = k.min(length - k);
+                    *value = if frequency == 0 { Complex::new(0.0, 0.0) } else { *value / (frequency as f64).sqrt() };
+                }
+                math::fft(&spectrum, true).iter().map(|c| c.re).collect()
+            },
+            NoiseKind::Brown => {
+                let mut sum = 0.0;
+                (0..length).map(|_| { sum += self.next_gaussian(); sum }).collect()
+            },
+        };
+        normalize(&samples).into_iter().map(cast).collect()
+    }
+}
+
+/// Adds noise of the given kind to the signal, scaled so that the signal-to-noise power ratio is `snr_db` decibels.
+/// The signal power is its mean square value (including any DC offset).
+/// Returns an error if the signal is empty, invalid or zero, or the SNR is not finite.
+pub fn add_noise<T: Real>(signal: &[T], kind: NoiseKind, snr_db: T, seed: u64) -> Result<Vec<T>, String> {
+    if signal.is_empty() { return Err("Input vector is empty".to_string()); }
+    if signal.iter().any(|val| !val.is_finite()) {
+        return Err("Input vector contains invalid values (NaN or Inf)".to_string());
+    }
+    if !snr_db.is_finite() { return Err(format!("SNR must be a finite number of dB, got {}", snr_db)); }
+    let signal_power = signal.iter().map(|&x| to_f64(x) * to_f64(x)).sum::<f64>() / signal.len() as f64;
+    if signal_power == 0.0 { return Err("Cannot set the SNR of a zero signal".to_string()); }
+
+    let noise_rms = (signal_power / 10f64.powf(to_f64(snr_db) / 10.0)).sqrt();
+    let noise: Vec<f64> = NoiseGenerator::new(seed).generate(kind, signal.len());
+    Ok(signal.iter().zip(noise.iter()).map(|(&x, &n)| x + cast(n * noise_rms)).collect())
+}
+
+/// Removes the mean and scales to unit RMS (a constant sequence becomes zeros).
+fn normalize(samples: &[f64]) -> Vec<f64> {
+    if samples.is_empty() { return Vec::new(); }
+    let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+    let rms = (samples.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / samples.len() as f64).sqrt();
+    let scale = if rms > 0.0 { 1.0 / rms } else { 0.0 };
+    samples.iter().map(|x| (x - mean) * scale).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Mean power of the DFT bins in [k_min, k_max), in dB.
+    fn band_power_db(samples: &[f64], k_min: usize, k_max: usize) -> f64 {
+        let widened: Vec<Complex<f64>> = samples.iter().map(|&x| Complex::new(x, 0.0)).collect();
+        let spectrum = math::fft(&widened, false);
+        let power = spectrum[k_min..k_max].iter().map(|c| c.norm_sqr()).sum::<f64>() / (k_max - k_min) as f64;
+        10.0 * power.log10()
+    }
+
+    #[test]
+    fn generator_is_deterministic() {
+        // Reference outputs of SplitMix64 seeded with 0
+        let mut generator = NoiseGenerator::new(0);
+        assert_eq!(generator.next_u64(), 0xE220_A839_7B1D_CDAF);
+        assert_eq!(generator.next_u64(), 0x6E78_9E6A_A1B9_65F4);
+
+        for kind in [NoiseKind::White, NoiseKind::Uniform, NoiseKind::Pink, NoiseKind::Brown] {
+            let first: Vec<f64> = NoiseGenerator::new(42).generate(kind, 256);
+            assert_eq!(first, NoiseGenerator::new(42).generate::<f64>(kind, 256), "{:?}", kind);
+            assert_ne!(first, NoiseGenerator::new(43).generate::<f64>(kind, 256), "{:?}", kind);
+        }
+        let signal = [0.5f32, -1.0, 2.0, 0.0];
+        assert_eq!(add_noise(&signal, NoiseKind::White, 10.0, 1), add_noise(&signal, NoiseKind::White, 10.0, 1));
+    }
+
+    #[test]
+    fn samples_have_the_expected_distribution() {
+        let mut generator = NoiseGenerator::new(5);
+        let count = 100_000;
+        let gaussian: Vec<f64> = (0..count).map(|_| generator.next_gaussian()).collect();
+        let mean = gaussian.iter().sum::<f64>() / count as f64;
+        let variance = gaussian.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / count as f64;
+        assert!(mean.abs() < 0.02 && (variance - 1.0).abs() < 0.02, "mean {}, variance {}", mean, variance);
+
+        let uniform: Vec<f64> = (0..count).map(|_| generator.next_uniform()).collect();
+        assert!(uniform.iter().all(|x| (0.0..1.0).contains(x)));
+        let mean = uniform.iter().sum::<f64>() / count as f64;
+        let variance = uniform.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / count as f64;
+        assert!((mean - 0.5).abs() < 0.01 && (variance - 1.0 / 12.0).abs() < 0.002, "mean {}, variance {}", mean, variance);
+
+        // Generated noise is normalized exactly
+        for kind in [NoiseKind::White, NoiseKind::Uniform, NoiseKind::Pink, NoiseKind::Brown] {
+            let noise: Vec<f64> = NoiseGenerator::new(9).generate(kind, 1000);
+            let mean = noise.iter().sum::<f64>() / noise.len() as f64;
+            let power = noise.iter().map(|x| x * x).sum::<f64>() / noise.len() as f64;
+            assert!(mean.abs() < 1e-12 && (power - 1.0).abs() < 1e-12, "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn colored_noise_has_the_expected_spectral_slope() {
+        // Six octaves between the bands: 0 dB for white, -18 dB for pink (1/f), -36 dB for brown (1/f²)
+        let n = 16384;
+        for (kind, expected) in [(NoiseKind::White, 0.0), (NoiseKind::Pink, -18.0), (NoiseKind::Brown, -36.0)] {
+            let noise: Vec<f64> = NoiseGenerator::new(11).generate(kind, n);
+            let drop = band_power_db(&noise, 1024, 2048) - band_power_db(&noise, 16, 32);
+            assert!((drop - expected).abs() < 3.0, "{:?}: {} dB", kind, drop);
+        }
+    }
+
+    #[test]
+    fn noise_is_added_at_the_requested_snr() {
+        let signal: Vec<f64> = (0..1000).map(|j| 3.0 * (0.05 * j as f64).sin()).collect();
+        let signal_power = signal.iter().map(|x| x * x).sum::<f64>() / signal.len() as f64;
+        for snr_db in [-10.0, 0.0, 20.0] {
+            let noisy = add_noise(&signal, NoiseKind::Pink, snr_db, 4).unwrap();
+            let noise_power = noisy.iter().zip(signal.iter()).map(|(y, x)| (y - x) * (y - x)).sum::<f64>() / signal.len() as f64;
+            assert!((10.0 * (signal_power / noise_power).log10() - snr_db).abs() < 1e-9, "SNR {}", snr_db);
+        }
+        assert!(add_noise::<f64>(&[], NoiseKind::White, 10.0, 1).is_err());
+        assert!(add_noise(&[0.0f64; 8], NoiseKind::White, 10.0, 1).is_err());
+        assert!(add_noise(&[1.0f64; 8], NoiseKind::White, f64::NAN, 1).is_err());
+    }
+}
@@ -125,13 +125,13 @@ impl Plotter {
         if x_data.len() < 2 {
             return Err("At least two data points are required to plot a line".to_string());
         }
-        self.data.push(FunctionData { style: FunctionType::LINE, x_data: x_data.to_vec(), y_data: y_data.to_vec(), color, line_width, bar_width: 0.0 });
+        self.data.push(FunctionData { style: FunctionType::LINE, x_data: x_data.to_vec(), y_data: y_data.to_vec(), color, line_width, bar_width: 0.0, z_data: Vec::new(), labels: Vec::new() });
         Ok(())
     }
 
     pub fn plot_arrow(&mut self, x_data: &[f32], y_data: &[f32], color: (u8, u8, u8), line_width: f32) -> Result<(), String> {
         if x_data.len() == 2 && y_data.len() == 2 {
-            self.data.push(FunctionData { style: FunctionType::ARROW, x_data: x_data.to_vec(), y_data: y_data.to_vec(), color, line_width, bar_width: 0.0 });
+            self.data.push(FunctionData { style: FunctionType::ARROW, x_data: x_data.to_vec(), y_data: y_data.to_vec(), color, line_width, bar_width: 0.0, z_data: Vec::new(), labels: Vec::new() });
             Ok(())
         } else {
             Err("x_data and y_data must have exactly two points for arrows".to_string())
@@ -152,7 +152,52 @@ impl Plotter {
             y_data: y_data.to_vec(),
             color,
             line_width: 1.0,
-            bar_width
+            bar_width,
+            z_data: Vec::new(),
+            labels: Vec::new(),
+        });
+
+        Ok(())
+    }
+
+    /// Plot a heatmap given x (column centers), y (row centers) and values, where
+    /// `values[i * y_data.len() + j]` is the value at (x_data[i], y_data[j]).
+    /// Values are mapped linearly from their min to their max onto a viridis-like color map.
+    pub fn plot_heatmap(&mut self, x_data: &[f32], y_data: &[f32], values: &[f32]) -> Result<(), String> {
+        if x_data.is_empty() || y_data.is_empty() {
+            return Err("At least one row and one column are required to plot a heatmap".to_string());
+        }
+        if values.len() != x_data.len() * y_data.len() {
+            return Err(format!("Expected {} heatmap values, got {}", x_data.len() * y_data.len(), values.len()));
+        }
+        self.data.push(FunctionData {
+            style: FunctionType::HEATMAP,
+            x_data: x_data.to_vec(),
+            y_data: y_data.to_vec(),
+            color: canvas::BLACK,
+            line_width: 0.0,
+            bar_width: 0.0,
+            z_data: values.to_vec(),
+            labels: Vec::new(),
+        });
+
+        Ok(())
+    }
+
+    /// Plot markers at the given points, each annotated with its label (labels may be empty)
+    pub fn plot_markers(&mut self, x_data: &[f32], y_data: &[f32], labels: &[String], color: (u8, u8, u8)) -> Result<(), String> {
+        if x_data.len() != y_data.len() || x_data.len() != labels.len() {
+            return Err("x_data, y_data and labels must have the same length".to_string());
+        }
+        self.data.push(FunctionData {
+            style: FunctionType::MARKERS,
+            x_data: x_data.to_vec(),
+            y_data: y_data.to_vec(),
+            color,
+            line_width: 0.0,
+            bar_width: 0.0,
+            z_data: Vec::new(),
+            labels: labels.to_vec(),
         });
 
         Ok(())
@@ -202,6 +247,10 @@ impl Plotter {
         }
 
         self.canvas.clear();
+        // Heatmaps are drawn first so the grid and axes remain visible on top of them
+        for func in self.data.iter().filter(|f| matches!(f.style, FunctionType::HEATMAP)) {
+            self.draw_heatmap(func);
+        }
         self.draw_grid();
         self.draw_axes();
 
@@ -241,7 +290,18 @@ impl Plotter {
                         let (x1, y1) = self.viewport_to_canvas(right_edge, y_data[i]);
                         self.canvas.fill_rect(x0, y0, x1-x0, y1 - y0, 0.0, *color);
                     }
-                }
+                },
+                FunctionType::MARKERS => {
+                    let font = format!("{}px {}", self.font_size, self.font_family);
+                    let radius = self.font_size / 3.0;
+                    for ((&x, &y), label) in func.x_data.iter().zip(func.y_data.iter()).zip(func.labels.iter()) {
+                        let (x_pixel, y_pixel) = self.viewport_to_canvas(x, y);
+                        self.canvas.fill_circle(x_pixel, y_pixel, radius, func.color);
+                        // Label above and to the right of the marker
+                        self.canvas.draw_text(label, x_pixel + radius, y_pixel - radius, &font, canvas::BLACK);
+                    }
+                },
+                FunctionType::HEATMAP => {} // Already drawn below the grid
             }
         }
     }
@@ -266,6 +326,27 @@ impl Plotter {
         (x_pos, y_pos)
     }
 
+    /// Draw a heatmap as one filled rectangle per cell, assuming evenly spaced centers
+    fn draw_heatmap(&self, func: &FunctionData) {
+        let spacing = |data: &[f32]| if data.len() > 1 { (data[data.len() - 1] - data[0]) / (data.len() - 1) as f32 } else { 1.0 };
+        let half_dx = spacing(&func.x_data) / 2.0;
+        let half_dy = spacing(&func.y_data) / 2.0;
+
+        let (min, max) = func.z_data.iter().fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+        let range = if max > min { max - min } else { 1.0 };
+
+        let rows = func.y_data.len();
+        for (i, &x) in func.x_data.iter().enumerate() {
+            for (j, &y) in func.y_data.iter().enumerate() {
+                let color = color_map((func.z_data[i * rows + j] - min) / range);
+                let (x0, y0) = self.viewport_to_canvas(x - half_dx, y - half_dy);
+                let (x1, y1) = self.viewport_to_canvas(x + half_dx, y + half_dy);
+                // Slightly oversized cells avoid seams between neighbours
+                self.canvas.fill_rect(x0, y1, x1 - x0 + 0.5, y0 - y1 + 0.5, 0.0, color);
+            }
+        }
+    }
+
     /// Display a text box with coordinates (in plotter space) at the top right of the plot.
     fn show_coordinates(&self, x: f32, y: f32) {
         // Format coordinates in plotter (math) space
@@ -371,6 +452,8 @@ enum FunctionType {
     LINE,
     ARROW,
     HISTOGRAM,
+    HEATMAP,
+    MARKERS,
 }
 
 /// Data structure for a single function to plot
@@ -385,6 +468,27 @@ struct FunctionData {
     line_width: f32,
     /// Histogram bar width (used for histogram style)
     bar_width: f32,
+    /// Cell values (used for heatmap style)
+    z_data: Vec<f32>,
+    /// Text next to each point (used for markers style)
+    labels: Vec<String>,
+}
+
+/// Map a value in [0, 1] to a color, interpolating between viridis-like anchors
+fn color_map(value: f32) -> (u8, u8, u8) {
+    const ANCHORS: [(f32, f32, f32); 5] = [
+        (68.0, 1.0, 84.0),
+        (59.0, 82.0, 139.0),
+        (33.0, 145.0, 140.0),
+        (94.0, 201.0, 98.0),
+        (253.0, 231.0, 37.0),
+    ];
+    let position = value.clamp(0.0, 1.0) * (ANCHORS.len() - 1) as f32;
+    let index = (position as usize).min(ANCHORS.len() - 2);
+    let t = position - index as f32;
+    let (r0, g0, b0) = ANCHORS[index];
+    let (r1, g1, b1) = ANCHORS[index + 1];
+    ((r0 + t * (r1 - r0)) as u8, (g0 + t * (g1 - g0)) as u8, (b0 + t * (b1 - b0)) as u8)
 }
 
 struct UpdateViewport {
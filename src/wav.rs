@@ -0,0 +1,346 @@
+#![allow(dead_code)]
+
+/// Decoder and encoder for RIFF/WAVE audio files.
+///
+/// Supports integer PCM with 8 (unsigned), 16, 24 or 32 bits per sample and 32-bit IEEE float.
+/// Decoding accepts the plain or the extensible format with any number of interleaved channels,
+/// converting samples to floats in [-1, 1], and skips chunks other than "fmt " and "data".
+/// Encoding writes a plain mono file.
+use crate::math::{to_f64, validate_signal, Real};
+
+/// Format tag of integer PCM samples.
+const FORMAT_PCM: u16 = 1;
+/// Format tag of IEEE float samples.
+const FORMAT_FLOAT: u16 = 3;
+/// Format tag of the extensible format, whose actual format is the start of the sub-format GUID.
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Encoding of the samples in the data chunk.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SampleFormat {
+    /// Integer PCM with the given number of bits per sample (8 is unsigned, others are signed).
+    Pcm(u16),
+    /// 32-bit IEEE float.
+    Float32,
+}
+
+impl SampleFormat {
+    /// Returns the number of bytes of one sample.
+    pub fn bytes_per_sample(&self) -> usize {
+        match *self {
+            SampleFormat::Pcm(bits) => bits as usize / 8,
+            SampleFormat::Float32 => 4,
+        }
+    }
+
+    /// Converts the little-endian bytes of one sample to a float in [-1, 1].
+    fn decode(&self, bytes: &[u8]) -> f32 {
+        match *self {
+            SampleFormat::Pcm(8) => (bytes[0] as f32 - 128.0) / 128.0,
+            SampleFormat::Pcm(16) => i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32_768.0,
+            // Place the 24 bits at the top of an i32 so the sign is extended
+            SampleFormat::Pcm(24) => i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) as f32 / 2_147_483_648.0,
+            SampleFormat::Pcm(_) => i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32 / 2_147_483_648.0,
+            SampleFormat::Float32 => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        }
+    }
+
+    /// Appends the little-endian bytes of one sample; integer formats clip it to [-1, 1] and round it.
+    fn encode(&self, sample: f64, bytes: &mut Vec<u8>) {
+        let scaled = |full_scale: f64| (sample.clamp(-1.0, 1.0) * full_scale).round().clamp(-full_scale, full_scale - 1.0);
+        match *self {
+            SampleFormat::Pcm(8) => bytes.push((scaled(128.0) + 128.0) as u8),
+            SampleFormat::Pcm(16) => bytes.extend_from_slice(&(scaled(32_768.0) as i16).to_le_bytes()),
+            SampleFormat::Pcm(24) => bytes.extend_from_slice(&(scaled(8_388_608.0) as i32).to_le_bytes()[..3]),
+            SampleFormat::Pcm(_) => bytes.extend_from_slice(&(scaled(2_147_483_648.0) as i32).to_le_bytes()),
+            SampleFormat::Float32 => bytes.extend_from_slice(&(sample as f32).to_le_bytes()),
+        }
+    }
+
+    /// Returns the format tag and number of bits of the "fmt " chunk.
+    fn tag_and_bits(&self) -> (u16, u16) {
+        match *self {
+            SampleFormat::Pcm(bits) => (FORMAT_PCM, bits),
+            SampleFormat::Float32 => (FORMAT_FLOAT, 32),
+        }
+    }
+}
+
+/// Decoded audio, stored channel by channel.
+pub struct WavAudio {
+    sample_rate: u32,
+    format: SampleFormat,
+    channels: Vec<Vec<f32>>,
+}
+
+impl WavAudio {
+    /// Returns the number of samples per second (of each channel).
+    pub fn sample_rate(&self) -> u32 { self.sample_rate }
+    /// Returns the encoding of the samples in the file.
+    pub fn format(&self) -> SampleFormat { self.format }
+    /// Returns the number of channels.
+    pub fn num_channels(&self) -> usize { self.channels.len() }
+    /// Returns the number of samples of each channel.
+    pub fn num_frames(&self) -> usize { self.channels[0].len() }
+    /// Returns the duration in seconds.
+    pub fn duration(&self) -> f32 { self.num_frames() as f32 / self.sample_rate as f32 }
+
+    /// Returns the samples of a channel (0-based, so 0 is the left channel of a stereo file), if it exists.
+    pub fn channel(&self, index: usize) -> Option<&[f32]> { self.channels.get(index).map(|channel| channel.as_slice()) }
+
+    /// Returns the average of all channels.
+    pub fn mixdown(&self) -> Vec<f32> {
+        let scale = 1.0 / self.channels.len() as f32;
+        (0..self.num_frames())
+            .map(|i| self.channels.iter().map(|channel| channel[i]).sum::<f32>() * scale)
+            .collect()
+    }
+}
+
+/// Decodes a RIFF/WAVE file.
+/// A data chunk longer than the file (as written by interrupted or streaming recorders) is cut to the whole frames present.
+/// Returns an error if the file is not a WAVE file, its format is not supported, or it contains no samples.
+pub fn decode(bytes: &[u8]) -> Result<WavAudio, String> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err("Not a WAV file (missing RIFF/WAVE header)".to_string());
+    }
+
+    let mut format = None;
+    let mut offset = 12;
+    while offset + 8 <= bytes.len() {
+        let id = &bytes[offset..offset + 4];
+        let size = read_u32(bytes, offset + 4) as usize;
+        let end = (offset + 8).saturating_add(size);
+        let body = &bytes[offset + 8..bytes.len().min(end)];
+        match id {
+            b"fmt " => format = Some(parse_format(body)?),
+            b"data" => {
+                let (sample_format, num_channels, sample_rate) = format.ok_or("The data chunk comes before the fmt chunk")?;
+                return decode_samples(body, sample_format, num_channels, sample_rate);
+            },
+            _ => {},
+        }
+        // Chunks are padded to an even size
+        offset = end.saturating_add(size % 2);
+    }
+    Err(if format.is_some() { "The file has no data chunk" } else { "The file has no fmt chunk" }.to_string())
+}
+
+/// Encodes mono samples (nominally in [-1, 1]) as a WAV file.
+/// Returns an error if there are no samples, a sample is invalid (or too large for float samples), the sample rate is zero,
+/// the format is not one that `decode` reads, or the file would exceed the 4 GiB limit of RIFF.
+pub fn encode<T: Real>(samples: &[T], sample_rate: u32, format: SampleFormat) -> Result<Vec<u8>, String> {
+    validate_signal(samples)?;
+    if format == SampleFormat::Float32 && samples.iter().any(|&sample| to_f64(sample).abs() > f32::MAX as f64) {
+        return Err("Input vector contains values beyond the float32 range".to_string());
+    }
+    if sample_rate == 0 { return Err("The sample rate is zero".to_string()); }
+    if let SampleFormat::Pcm(bits) = format {
+        if ![8, 16, 24, 32].contains(&bits) { return Err(format!("Unsupported PCM sample size: {} bits", bits)); }
+    }
+    let sample_size = format.bytes_per_sample();
+    let data_size = u32::try_from(samples.len() * sample_size)
+        .ok()
+        .filter(|&size| size < u32::MAX - 36)
+        .ok_or("Too many samples for a WAV file")?;
+    let (tag, bits) = format.tag_and_bits();
+
+    let mut bytes = Vec::with_capacity(44 + data_size as usize + 1);
+    bytes.extend_from_slice(b"RIFF");
+    // The data chunk is padded to an even size
+    bytes.extend_from_slice(&(36 + data_size + data_size % 2).to_le_bytes());
+    bytes.extend_from_slice(b"WAVE");
+    bytes.extend_from_slice(b"fmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&tag.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&sample_rate.to_le_bytes());
+    bytes.extend_from_slice(&(sample_rate.wrapping_mul(sample_size as u32)).to_le_bytes());
+    bytes.extend_from_slice(&(sample_size as u16).to_le_bytes());
+    bytes.extend_from_slice(&bits.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_size.to_le_bytes());
+    for &sample in samples {
+        format.encode(to_f64(sample), &mut bytes);
+    }
+    if data_size % 2 == 1 { bytes.push(0); }
+    Ok(bytes)
+}
+
+/// Reads the sample format, number of channels and sample rate from the body of a "fmt " chunk.
+fn parse_format(body: &[u8]) -> Result<(SampleFormat, usize, u32), String> {
+    if body.len() < 16 { return Err("The fmt chunk is too short".to_string()); }
+    let mut tag = read_u16(body, 0);
+    let num_channels = read_u16(body, 2) as usize;
+    let sample_rate = read_u32(body, 4);
+    let bits = read_u16(body, 14);
+    if tag == FORMAT_EXTENSIBLE {
+        if body.len() < 26 { return Err("The extensible fmt chunk is too short".to_string()); }
+        tag = read_u16(body, 24);
+    }
+
+    let sample_format = match (tag, bits) {
+        (FORMAT_PCM, 8 | 16 | 24 | 32) => SampleFormat::Pcm(bits),
+        (FORMAT_FLOAT, 32) => SampleFormat::Float32,
+        (FORMAT_PCM, _) => return Err(format!("Unsupported PCM sample size: {} bits", bits)),
+        (FORMAT_FLOAT, _) => return Err(format!("Unsupported float sample size: {} bits", bits)),
+        _ => return Err(format!("Unsupported WAV format tag {} (only PCM and IEEE float are supported)", tag)),
+    };
+    if num_channels == 0 { return Err("The file has no channels".to_string()); }
+    if sample_rate == 0 { return Err("The sample rate is zero".to_string()); }
+    Ok((sample_format, num_channels, sample_rate))
+}
+
+/// De-interleaves the samples of a data chunk.
+fn decode_samples(data: &[u8], format: SampleFormat, num_channels: usize, sample_rate: u32) -> Result<WavAudio, String> {
+    let sample_size = format.bytes_per_sample();
+    let frame_size = sample_size * num_channels;
+    let num_frames = data.len() / frame_size;
+    if num_frames == 0 { return Err("The file contains no samples".to_string()); }
+
+    let mut channels: Vec<Vec<f32>> = (0..num_channels).map(|_| Vec::with_capacity(num_frames)).collect();
+    for frame in data.chunks_exact(frame_size) {
+        for (channel, sample) in channels.iter_mut().zip(frame.chunks_exact(sample_size)) {
+            channel.push(format.decode(sample));
+        }
+    }
+    if channels.iter().flatten().any(|sample| !sample.is_finite()) {
+        return Err("The file contains invalid samples (NaN or Inf)".to_string());
+    }
+    Ok(WavAudio { sample_rate, format, channels })
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a file with a single "fmt " chunk (plain or extensible), optionally preceded by another chunk.
+    fn file(tag: u16, num_channels: u16, sample_rate: u32, bits: u16, data: &[u8], extensible: bool, extra_chunk: bool) -> Vec<u8> {
+        let block_align = num_channels * bits / 8;
+        let mut format = Vec::new();
+        format.extend_from_slice(&(if extensible { FORMAT_EXTENSIBLE } else { tag }).to_le_bytes());
+        format.extend_from_slice(&num_channels.to_le_bytes());
+        format.extend_from_slice(&sample_rate.to_le_bytes());
+        format.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+        format.extend_from_slice(&block_align.to_le_bytes());
+        format.extend_from_slice(&bits.to_le_bytes());
+        if extensible {
+            format.extend_from_slice(&22u16.to_le_bytes());
+            format.extend_from_slice(&bits.to_le_bytes());
+            format.extend_from_slice(&0u32.to_le_bytes());
+            format.extend_from_slice(&tag.to_le_bytes());
+            format.extend_from_slice(&[0; 14]);
+        }
+
+        let mut bytes = b"RIFF\0\0\0\0WAVE".to_vec();
+        if extra_chunk {
+            // Odd-sized chunk followed by its pad byte
+            bytes.extend_from_slice(b"LIST");
+            bytes.extend_from_slice(&3u32.to_le_bytes());
+            bytes.extend_from_slice(&[1, 2, 3, 0]);
+        }
+        bytes.extend_from_slice(b"fmt ");
+        bytes.extend_from_slice(&(format.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&format);
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    #[test]
+    fn encoding_round_trips() {
+        let samples: Vec<f32> = (0..101).map(|i| (0.3 * i as f32).sin() * 0.9).chain([1.0, -1.0, 0.0]).collect();
+        let formats = [
+            (SampleFormat::Pcm(8), 1.0 / 128.0),
+            (SampleFormat::Pcm(16), 1.0 / 32_768.0),
+            (SampleFormat::Pcm(24), 1.0 / 8_388_608.0),
+            (SampleFormat::Pcm(32), 1e-7),
+            (SampleFormat::Float32, 0.0),
+        ];
+        for (format, tolerance) in formats {
+            let bytes = encode(&samples, 22_050, format).unwrap();
+            // 8-bit files have an odd data size and get a pad byte
+            assert_eq!(bytes.len(), 44 + (samples.len() * format.bytes_per_sample()).next_multiple_of(2));
+            assert_eq!(read_u32(&bytes, 4) as usize, bytes.len() - 8);
+
+            let audio = decode(&bytes).unwrap();
+            assert_eq!(audio.format(), format);
+            assert_eq!(audio.sample_rate(), 22_050);
+            assert_eq!(audio.num_channels(), 1);
+            assert_eq!(audio.num_frames(), samples.len());
+            for (i, (&decoded, &sample)) in audio.channel(0).unwrap().iter().zip(samples.iter()).enumerate() {
+                // Full scale clips to the largest positive code
+                assert!((decoded - sample).abs() <= tolerance, "{:?} sample {}: {} != {}", format, i, decoded, sample);
+            }
+        }
+
+        // Out-of-range samples are clipped by the integer formats only
+        let audio = decode(&encode(&[2.0, -3.0], 8000, SampleFormat::Pcm(16)).unwrap()).unwrap();
+        assert_eq!(audio.channel(0).unwrap(), &[32_767.0 / 32_768.0, -1.0]);
+        let audio = decode(&encode(&[2.0, -3.0], 8000, SampleFormat::Float32).unwrap()).unwrap();
+        assert_eq!(audio.channel(0).unwrap(), &[2.0, -3.0]);
+    }
+
+    #[test]
+    fn multichannel_and_extensible_files_are_decoded() {
+        let data: Vec<u8> = [0i16, 16_384, -32_768, 32_767].iter().flat_map(|x| x.to_le_bytes()).collect();
+        let audio = decode(&file(FORMAT_PCM, 2, 44_100, 16, &data, false, true)).unwrap();
+        assert_eq!(audio.num_channels(), 2);
+        assert_eq!(audio.num_frames(), 2);
+        assert_eq!(audio.sample_rate(), 44_100);
+        assert_eq!(audio.channel(0).unwrap(), &[0.0, -1.0]);
+        assert_eq!(audio.channel(1).unwrap(), &[0.5, 32_767.0 / 32_768.0]);
+        assert!(audio.channel(2).is_none());
+        assert_eq!(audio.mixdown()[0], 0.25);
+
+        let audio = decode(&file(FORMAT_PCM, 1, 8000, 8, &[128, 0, 255], false, false)).unwrap();
+        assert_eq!(audio.channel(0).unwrap(), &[0.0, -1.0, 127.0 / 128.0]);
+
+        let data = [0x00, 0x00, 0x80, 0xff, 0xff, 0x7f, 0x00, 0x00, 0x40];
+        let audio = decode(&file(FORMAT_PCM, 1, 48_000, 24, &data, true, false)).unwrap();
+        assert_eq!(audio.format(), SampleFormat::Pcm(24));
+        assert_eq!(audio.channel(0).unwrap()[0], -1.0);
+        assert_eq!(audio.channel(0).unwrap()[2], 0.5);
+
+        let data: Vec<u8> = [0.25f32, -0.5].iter().flat_map(|x| x.to_le_bytes()).collect();
+        let audio = decode(&file(FORMAT_FLOAT, 1, 1000, 32, &data, true, false)).unwrap();
+        assert_eq!(audio.format(), SampleFormat::Float32);
+        assert_eq!(audio.channel(0).unwrap(), &[0.25, -0.5]);
+    }
+
+    #[test]
+    fn truncated_data_is_cut_to_whole_frames() {
+        let mut bytes = file(FORMAT_PCM, 1, 8000, 16, &[1, 0, 2, 0, 3], false, false);
+        assert_eq!(decode(&bytes).unwrap().num_frames(), 2);
+        // A data size beyond the end of the file, as left by an interrupted recording
+        let size_offset = bytes.len() - 9;
+        bytes[size_offset..size_offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(decode(&bytes).unwrap().num_frames(), 2);
+    }
+
+    #[test]
+    fn invalid_files_are_rejected() {
+        assert!(decode(b"nope").is_err());
+        assert!(decode(&file(FORMAT_PCM, 1, 8000, 12, &[0, 0], false, false)).err().unwrap().contains("12 bits"));
+        assert!(decode(&file(2, 1, 8000, 16, &[0, 0], false, false)).err().unwrap().contains("format tag 2"));
+        assert!(decode(&file(FORMAT_PCM, 0, 8000, 16, &[0, 0], false, false)).is_err());
+        assert!(decode(&file(FORMAT_PCM, 1, 0, 16, &[0, 0], false, false)).is_err());
+        assert!(decode(&file(FORMAT_PCM, 1, 8000, 16, &[0], false, false)).is_err());
+
+        assert!(encode::<f32>(&[], 8000, SampleFormat::Pcm(16)).is_err());
+        assert!(encode(&[f32::NAN], 8000, SampleFormat::Pcm(16)).is_err());
+        assert!(encode(&[0.0], 0, SampleFormat::Pcm(16)).is_err());
+        assert!(encode(&[0.0], 8000, SampleFormat::Pcm(12)).is_err());
+        assert!(encode(&[1e300], 8000, SampleFormat::Float32).is_err());
+        assert!(encode(&[1e300], 8000, SampleFormat::Pcm(16)).is_ok());
+    }
+}
@@ -0,0 +1,412 @@
+#![allow(dead_code)]
+
+/// Discrete wavelet transform (DWT) with orthogonal Haar and Daubechies wavelets.
+///
+/// Each level splits the signal into a low-pass approximation and a high-pass detail, both
+/// downsampled by 2. The signal is extended periodically at its edges ("periodization"), which
+/// keeps the transform orthogonal: N samples give N coefficients with the same energy, and the
+/// inverse is exact. Odd lengths are made even by repeating the last sample before a level.
+use crate::filter;
+use crate::math::{self, cast, to_f64, validate_signal, ConvolutionMode, Real};
+
+/// Highest Daubechies order available (number of vanishing moments).
+const MAX_DAUBECHIES_ORDER: u32 = 8;
+
+/// Median absolute deviation of Gaussian noise divided by its standard deviation.
+const MAD_TO_SIGMA: f64 = 0.6745;
+
+/// Orthogonal wavelet family.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Wavelet {
+    /// Two-tap wavelet, a step function (same as Daubechies(1)).
+    Haar,
+    /// Daubechies wavelet with the given number of vanishing moments (1..=8), with 2 * order taps.
+    Daubechies(u32),
+}
+
+impl Wavelet {
+    /// Returns an error if the Daubechies order is out of range.
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            Wavelet::Daubechies(order) if order == 0 || order > MAX_DAUBECHIES_ORDER =>
+                Err(format!("Daubechies order must be within [1, {}], got {}", MAX_DAUBECHIES_ORDER, order)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the number of taps of the filters.
+    pub fn filter_length(&self) -> usize {
+        match *self {
+            Wavelet::Haar => 2,
+            Wavelet::Daubechies(order) => 2 * order as usize,
+        }
+    }
+
+    /// Returns the low-pass (scaling) filter, normalized so its taps sum to sqrt(2).
+    ///
+    /// Daubechies filters are built by spectral factorization: the squared magnitude response
+    /// ((1 + cos w) / 2)^p P(sin²(w/2)) is split into its minimum-phase factor, keeping the
+    /// zeros of P inside the unit circle.
+    pub fn scaling_filter(&self) -> Vec<f64> {
+        let order = match *self {
+            Wavelet::Haar => 1,
+            Wavelet::Daubechies(order) => order as usize,
+        };
+
+        // (1 + z^-1)^p
+        let mut taps = vec![1.0];
+        for _ in 0..order {
+            taps = product(&taps, &[1.0, 1.0]);
+        }
+
+        if order > 1 {
+            // P(y) = sum_k C(p - 1 + k, k) y^k, made monic for the root finder
+            let mut binomial = 1.0;
+            let mut coefficients = Vec::with_capacity(order);
+            for k in 0..order {
+                coefficients.push(binomial);
+                binomial *= (order + k) as f64 / (k + 1) as f64;
+            }
+            let leading = coefficients[order - 1];
+            coefficients.iter_mut().for_each(|c| *c /= leading);
+
+            // Each root y gives z + 1/z = 2 - 4y; keep the solution z inside the unit circle
+            let mut factor = vec![num_complex::Complex64::new(1.0, 0.0)];
+            for y in filter::polynomial_roots(&coefficients) {
+                let sum = 2.0 - 4.0 * y;
+                let discriminant = (sum * sum - 4.0).sqrt();
+                let (first, second) = ((sum + discriminant) / 2.0, (sum - discriminant) / 2.0);
+                let z = if first.norm() < second.norm() { first } else { second };
+                // Multiply by (1 - z z^-1)
+                let mut next = factor.clone();
+                next.push(num_complex::Complex64::new(0.0, 0.0));
+                for i in 1..next.len() {
+                    next[i] -= z * factor[i - 1];
+                }
+                factor = next;
+            }
+            // The roots come in conjugate pairs, so the product is real
+            let factor: Vec<f64> = factor.iter().map(|c| c.re).collect();
+            taps = product(&taps, &factor);
+        }
+
+        let sum: f64 = taps.iter().sum();
+        taps.iter_mut().for_each(|h| *h *= std::f64::consts::SQRT_2 / sum);
+        taps
+    }
+
+    /// Returns the high-pass (wavelet) filter g[k] = (-1)^k h[L - 1 - k], the quadrature mirror of the scaling filter.
+    pub fn wavelet_filter(&self) -> Vec<f64> {
+        let scaling = self.scaling_filter();
+        scaling.iter().rev().enumerate()
+            .map(|(k, &h)| if k.is_multiple_of(2) { h } else { -h })
+            .collect()
+    }
+
+    /// Returns the deepest useful decomposition level for a signal of the given length,
+    /// i.e. the last level whose input is still at least as long as the filters.
+    pub fn max_level(&self, signal_length: usize) -> usize {
+        let taps = self.filter_length();
+        let mut length = signal_length;
+        let mut level = 0;
+        while length >= taps && length >= 2 {
+            length = length.div_ceil(2);
+            level += 1;
+        }
+        level
+    }
+}
+
+/// How `WaveletDecomposition::threshold` treats the detail coefficients.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Thresholding {
+    /// Zero the coefficients below the threshold and keep the others.
+    Hard,
+    /// Zero the coefficients below the threshold and shrink the others towards zero by it.
+    Soft,
+}
+
+/// Computes one level of the DWT, returning the approximation and detail coefficients
+/// (ceil(N / 2) each).
+/// Returns an error if the input is empty or invalid, or the wavelet is invalid.
+pub fn dwt<T: Real>(data: &[T], wavelet: Wavelet) -> Result<(Vec<T>, Vec<T>), String> {
+    validate_signal(data)?;
+    wavelet.validate()?;
+    Ok(analyze(data, &wavelet.scaling_filter(), &wavelet.wavelet_filter()))
+}
+
+/// Inverts one level of the DWT, returning `output_length` samples (2 * coefficients, or one less
+/// if the original length was odd).
+/// Returns an error if the coefficients have different lengths or do not match the output length.
+pub fn idwt<T: Real>(approximation: &[T], detail: &[T], wavelet: Wavelet, output_length: usize) -> Result<Vec<T>, String> {
+    wavelet.validate()?;
+    if approximation.len() != detail.len() {
+        return Err(format!("Approximation and detail lengths differ ({} and {})", approximation.len(), detail.len()));
+    }
+    if approximation.is_empty() || output_length.div_ceil(2) != approximation.len() {
+        return Err(format!("{} coefficients cannot reconstruct {} samples", approximation.len(), output_length));
+    }
+    let mut result = synthesize(approximation, detail, &wavelet.scaling_filter(), &wavelet.wavelet_filter());
+    result.truncate(output_length);
+    Ok(result)
+}
+
+/// Multi-level DWT of a real signal: the details of every level and the final approximation.
+pub struct WaveletDecomposition<T: Real = f32> {
+    wavelet: Wavelet,
+    // Detail coefficients, finest level (1) first.
+    details: Vec<Vec<T>>,
+    approximation: Vec<T>,
+    // Input length of every level, to undo the padding of odd lengths.
+    lengths: Vec<usize>,
+}
+
+impl<T: Real> WaveletDecomposition<T> {
+    /// Decomposes the signal over the given number of levels.
+    /// Returns an error if the input or the wavelet is invalid, or if `levels` is zero or larger
+    /// than `Wavelet::max_level`.
+    pub fn new(data: &[T], wavelet: Wavelet, levels: usize) -> Result<Self, String> {
+        validate_signal(data)?;
+        wavelet.validate()?;
+        let max_level = wavelet.max_level(data.len());
+        if levels == 0 || levels > max_level {
+            return Err(format!("Decomposition level must be within [1, {}], got {}", max_level, levels));
+        }
+
+        let scaling = wavelet.scaling_filter();
+        let detail_filter = wavelet.wavelet_filter();
+        let mut approximation = data.to_vec();
+        let mut details = Vec::with_capacity(levels);
+        let mut lengths = Vec::with_capacity(levels);
+        for _ in 0..levels {
+            lengths.push(approximation.len());
+            let (next, detail) = analyze(&approximation, &scaling, &detail_filter);
+            details.push(detail);
+            approximation = next;
+        }
+        Ok(WaveletDecomposition { wavelet, details, approximation, lengths })
+    }
+
+    /// Reconstructs the signal from the (possibly thresholded) coefficients.
+    pub fn reconstruct(&self) -> Vec<T> {
+        let scaling = self.wavelet.scaling_filter();
+        let detail_filter = self.wavelet.wavelet_filter();
+        let mut approximation = self.approximation.clone();
+        for (detail, &length) in self.details.iter().zip(self.lengths.iter()).rev() {
+            approximation = synthesize(&approximation, detail, &scaling, &detail_filter);
+            approximation.truncate(length);
+        }
+        approximation
+    }
+
+    /// Applies the threshold to the detail coefficients of every level; the approximation is kept.
+    pub fn threshold(&mut self, threshold: T, mode: Thresholding) {
+        for value in self.details.iter_mut().flatten() {
+            let magnitude = value.abs();
+            *value = if magnitude <= threshold { T::zero() } else {
+                match mode {
+                    Thresholding::Hard => *value,
+                    Thresholding::Soft => value.signum() * (magnitude - threshold),
+                }
+            };
+        }
+    }
+
+    /// Returns the universal threshold sigma * sqrt(2 ln N) of Donoho and Johnstone, with the noise level
+    /// sigma estimated from the median absolute value of the finest details.
+    pub fn universal_threshold(&self) -> T {
+        let mut magnitudes: Vec<f64> = self.details[0].iter().map(|&d| to_f64(d).abs()).collect();
+        magnitudes.sort_by(|a, b| a.total_cmp(b));
+        let middle = magnitudes.len() / 2;
+        let median = if magnitudes.len().is_multiple_of(2) { 0.5 * (magnitudes[middle - 1] + magnitudes[middle]) } else { magnitudes[middle] };
+        let sigma = median / MAD_TO_SIGMA;
+        cast(sigma * (2.0 * (self.lengths[0] as f64).ln()).sqrt())
+    }
+
+    /// Returns the coefficient magnitudes as a grid of `signal length` columns by `levels` rows, for a scalogram:
+    /// `values[n * levels + (j - 1)]` is |detail of level j| at the coefficient covering sample n.
+    pub fn scalogram(&self) -> Vec<T> {
+        let columns = self.lengths[0];
+        let levels = self.levels();
+        let mut values = Vec::with_capacity(columns * levels);
+        for n in 0..columns {
+            for (j, detail) in self.details.iter().enumerate() {
+                // Coefficient i of level j + 1 covers samples [i 2^(j+1), (i + 1) 2^(j+1))
+                let index = (n >> (j + 1)).min(detail.len() - 1);
+                values.push(detail[index].abs());
+            }
+        }
+        values
+    }
+
+    /// Returns the detail coefficients of the given level (1 is the finest).
+    pub fn detail(&self, level: usize) -> &[T] { &self.details[level - 1] }
+    /// Returns the approximation coefficients of the deepest level.
+    pub fn approximation(&self) -> &[T] { &self.approximation }
+    /// Returns the number of levels.
+    pub fn levels(&self) -> usize { self.details.len() }
+    /// Returns the wavelet.
+    pub fn wavelet(&self) -> Wavelet { self.wavelet }
+}
+
+/// Removes noise by soft-thresholding the details at the universal threshold.
+/// Returns an error if the decomposition cannot be computed (see `WaveletDecomposition::new`).
+pub fn denoise<T: Real>(data: &[T], wavelet: Wavelet, levels: usize) -> Result<Vec<T>, String> {
+    let mut decomposition = WaveletDecomposition::new(data, wavelet, levels)?;
+    let threshold = decomposition.universal_threshold();
+    decomposition.threshold(threshold, Thresholding::Soft);
+    Ok(decomposition.reconstruct())
+}
+
+/// One analysis step with periodization: a[i] = sum_k h[k] x[(2i + k) mod N], and likewise for the detail.
+fn analyze<T: Real>(data: &[T], scaling: &[f64], detail_filter: &[f64]) -> (Vec<T>, Vec<T>) {
+    let padded = even_length(data);
+    let n = padded.len();
+    let half = n / 2;
+    let mut approximation = Vec::with_capacity(half);
+    let mut detail = Vec::with_capacity(half);
+    for i in 0..half {
+        let (mut a, mut d) = (0.0, 0.0);
+        for (k, (&h, &g)) in scaling.iter().zip(detail_filter.iter()).enumerate() {
+            let x = padded[(2 * i + k) % n];
+            a += h * x;
+            d += g * x;
+        }
+        approximation.push(cast(a));
+        detail.push(cast(d));
+    }
+    (approximation, detail)
+}
+
+/// One synthesis step, the transpose of `analyze`: returns 2 * len samples.
+fn synthesize<T: Real>(approximation: &[T], detail: &[T], scaling: &[f64], detail_filter: &[f64]) -> Vec<T> {
+    let n = 2 * approximation.len();
+    let mut result = vec![0.0; n];
+    for (i, (&a, &d)) in approximation.iter().zip(detail.iter()).enumerate() {
+        let (a, d) = (to_f64(a), to_f64(d));
+        for (k, (&h, &g)) in scaling.iter().zip(detail_filter.iter()).enumerate() {
+            result[(2 * i + k) % n] += h * a + g * d;
+        }
+    }
+    result.into_iter().map(cast).collect()
+}
+
+/// Converts the samples to double precision, repeating the last one if the length is odd.
+fn even_length<T: Real>(data: &[T]) -> Vec<f64> {
+    let mut padded: Vec<f64> = data.iter().map(|&x| to_f64(x)).collect();
+    if !padded.len().is_multiple_of(2) { padded.push(padded[padded.len() - 1]); }
+    padded
+}
+
+/// Multiplies two polynomials in z^-1, i.e. the full convolution of their coefficients.
+fn product(a: &[f64], b: &[f64]) -> Vec<f64> {
+    // The coefficients are finite and non-empty, so the convolution cannot fail
+    math::convolve(a, b, ConvolutionMode::Full).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Odd and even lengths, including lengths padded at several levels.
+    const LENGTHS: [usize; 7] = [1, 3, 7, 16, 33, 101, 127];
+
+    /// Every supported wavelet.
+    fn wavelets() -> Vec<Wavelet> {
+        let mut wavelets = vec![Wavelet::Haar];
+        wavelets.extend((1..=MAX_DAUBECHIES_ORDER).map(Wavelet::Daubechies));
+        wavelets
+    }
+
+    /// A deterministic signal without any particular structure.
+    fn test_signal(n: usize) -> Vec<f64> {
+        (0..n).map(|i| (0.37 * i as f64).sin() + 0.5 * (1.9 * i as f64 + 0.3).cos() + 0.01 * i as f64).collect()
+    }
+
+    fn assert_close(actual: &[f64], expected: &[f64], tolerance: f64) {
+        assert_eq!(actual.len(), expected.len());
+        for (i, (a, e)) in actual.iter().zip(expected.iter()).enumerate() {
+            assert!((a - e).abs() < tolerance, "sample {}: {} != {}", i, a, e);
+        }
+    }
+
+    #[test]
+    fn filters_are_orthonormal() {
+        for wavelet in wavelets() {
+            let h = wavelet.scaling_filter();
+            let g = wavelet.wavelet_filter();
+            assert_eq!(h.len(), wavelet.filter_length());
+            // sum h[k] h[k + 2m] = delta[m], and likewise for g; h and g are orthogonal at every even shift
+            for shift in (0..h.len()).step_by(2) {
+                let hh: f64 = (0..h.len() - shift).map(|k| h[k] * h[k + shift]).sum();
+                let gg: f64 = (0..g.len() - shift).map(|k| g[k] * g[k + shift]).sum();
+                let expected = if shift == 0 { 1.0 } else { 0.0 };
+                assert!((hh - expected).abs() < 1e-9, "{:?} shift {}: {}", wavelet, shift, hh);
+                assert!((gg - expected).abs() < 1e-9, "{:?} shift {}: {}", wavelet, shift, gg);
+            }
+            let hg: f64 = h.iter().zip(g.iter()).map(|(a, b)| a * b).sum();
+            assert!(hg.abs() < 1e-9, "{:?}: {}", wavelet, hg);
+        }
+
+        // Daubechies 2 in closed form: (1 + sqrt3, 3 + sqrt3, 3 - sqrt3, 1 - sqrt3) / (4 sqrt2)
+        let sqrt3 = 3f64.sqrt();
+        let scale = 4.0 * std::f64::consts::SQRT_2;
+        let expected = [(1.0 + sqrt3) / scale, (3.0 + sqrt3) / scale, (3.0 - sqrt3) / scale, (1.0 - sqrt3) / scale];
+        assert_close(&Wavelet::Daubechies(2).scaling_filter(), &expected, 1e-12);
+    }
+
+    #[test]
+    fn single_level_reconstructs_any_length() {
+        for wavelet in wavelets() {
+            for &n in &LENGTHS {
+                let data = test_signal(n);
+                let (approximation, detail) = dwt(&data, wavelet).unwrap();
+                assert_eq!(approximation.len(), n.div_ceil(2));
+                assert_eq!(detail.len(), n.div_ceil(2));
+                let result = idwt(&approximation, &detail, wavelet, n).unwrap();
+                assert_close(&result, &data, 1e-9);
+
+                // Periodization keeps the energy of the (padded) input
+                if n.is_multiple_of(2) {
+                    let energy: f64 = data.iter().map(|x| x * x).sum();
+                    let coefficient_energy: f64 = approximation.iter().chain(detail.iter()).map(|x| x * x).sum();
+                    assert!((energy - coefficient_energy).abs() < 1e-9 * energy, "{:?} N = {}", wavelet, n);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn multi_level_reconstructs_any_length() {
+        for wavelet in wavelets() {
+            for &n in &LENGTHS {
+                let data = test_signal(n);
+                for levels in 1..=wavelet.max_level(n) {
+                    let decomposition = WaveletDecomposition::new(&data, wavelet, levels).unwrap();
+                    assert_eq!(decomposition.levels(), levels);
+                    assert_close(&decomposition.reconstruct(), &data, 1e-9);
+                }
+            }
+        }
+
+        // Single precision reconstructs to its own accuracy
+        let data: Vec<f32> = test_signal(101).iter().map(|&x| x as f32).collect();
+        let decomposition = WaveletDecomposition::new(&data, Wavelet::Daubechies(4), 3).unwrap();
+        for (a, e) in decomposition.reconstruct().iter().zip(data.iter()) {
+            assert!((a - e).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn invalid_input_is_rejected() {
+        assert!(dwt::<f64>(&[], Wavelet::Haar).is_err());
+        assert!(dwt(&[1.0, f64::NAN], Wavelet::Haar).is_err());
+        assert!(dwt(&[1.0, 2.0], Wavelet::Daubechies(0)).is_err());
+        assert!(dwt(&[1.0, 2.0], Wavelet::Daubechies(MAX_DAUBECHIES_ORDER + 1)).is_err());
+        assert!(idwt(&[1.0], &[1.0, 2.0], Wavelet::Haar, 2).is_err());
+        assert!(idwt(&[1.0, 2.0], &[1.0, 2.0], Wavelet::Haar, 5).is_err());
+        let data = test_signal(33);
+        assert!(WaveletDecomposition::new(&data, Wavelet::Haar, 0).is_err());
+        assert!(WaveletDecomposition::new(&data, Wavelet::Haar, Wavelet::Haar.max_level(33) + 1).is_err());
+    }
+}
@@ -4,14 +4,48 @@ import { getCanvasImports } from './canvas-wasm.js';
 
 
 interface FourierExports extends WasmExports {
-    plot_example: (kMin: number, kMax: number, kind: number) => void;
+    plot_example: (kMin: number, kMax: number, kind: number) => boolean;
+    replot_example: (kMin: number, kMax: number) => boolean;
+    alloc_f32_buffer: (len: number) => number;
+    free_buffer: (ptr: number, len: number) => void;
+    plot_signal: (ptr: number, len: number, sampleRate: number, kMin: number, kMax: number) => boolean;
+    alloc_u8_buffer: (len: number) => number;
+    free_u8_buffer: (ptr: number, len: number) => void;
+    load_csv: (ptr: number, len: number, timeColumn: number, valueColumn: number, imaginaryColumn: number,
+               defaultSampleRate: number, kMin: number, kMax: number) => boolean;
+    load_wav: (ptr: number, len: number, channel: number, kMin: number, kMax: number) => boolean;
+    plot_expression: (ptr: number, len: number, tStart: number, tEnd: number, numSamples: number,
+                      kMin: number, kMax: number) => boolean;
+    export_wav: (format: number, normalize: boolean) => boolean;
+    export_wav_ptr: () => number;
+    export_wav_len: () => number;
+    last_error_ptr: () => number;
+    last_error_len: () => number;
+    set_spectrum_window: (kind: number, parameter: number) => boolean;
+    set_spectrum_padding: (factor: number) => boolean;
+    set_spectrum_estimator: (kind: number) => boolean;
+    set_spectrum_peaks: (count: number) => boolean;
+    set_spectrum_phase: (view: number) => boolean;
+    set_scalogram_wavelet: (order: number) => boolean;
+    set_example_envelope: (show: boolean) => void;
+    set_example_range_shape: (kind: number, parameter: number) => boolean;
+    set_example_basis: (kind: number) => boolean;
+    set_example_noise: (kind: number, snrDb: number, seed: number) => boolean;
+    example_period: () => number;
+    example_quality: (metric: number, filtered: boolean) => number;
+    set_example_filter: (design: number, response: number, order: number, cutoff: number, cutoffHigh: number, zeroPhase: boolean) => boolean;
     step_animation: () => void;
     play_pause_animation: (kMin: number, kMax: number, example: number) => void;
     stop_animation: () => void;
     increase_animation_speed: () => void;
     decrease_animation_speed: () => void;
+    load_image: (ptr: number, width: number, height: number, channels: number) => boolean;
+    filter_image: (kind: number, radius: number, outerRadius: number) => boolean;
 }
 
+// Images are downscaled so the 2D transform and per-pixel drawing stay interactive
+const MAX_IMAGE_SIZE = 256;
+
 /////////////////////////////////////////////////////////////////////////////////////////
 /////////////////////////////////////////////////////////////////////////////////////////
 /////////////////////////////////////////////////////////////////////////////////////////
@@ -26,12 +60,107 @@ document.addEventListener('DOMContentLoaded', async () => {
     const exampleFreqMinInput = document.getElementById('example-freq-min')! as HTMLInputElement;
     const exampleMaxFreqInput = document.getElementById('example-freq-max')! as HTMLInputElement;
 
+    const exampleQualitySpan = document.getElementById('example-quality')! as HTMLSpanElement;
+    const examplePeriodSpan = document.getElementById('example-period')! as HTMLSpanElement;
+    const exampleErrorSpan = document.getElementById('example-error')! as HTMLSpanElement;
+
+    // Read the message of the last failed call
+    function lastError(): string {
+        const bytes = new Uint8Array(WASM.memory.buffer, WASM.last_error_ptr(), WASM.last_error_len());
+        return new TextDecoder().decode(bytes);
+    }
+
+    // Show the message of the last failed call (or clear it after a successful one)
+    function showExampleResult(ok: boolean) {
+        exampleErrorSpan.textContent = ok ? '' : lastError();
+    }
+
+    // Report how the filter changes the quality metrics of the signal
+    function showExampleQuality() {
+        const metric = (index: number, unit: string) => {
+            const before = WASM.example_quality(index, false);
+            const after = WASM.example_quality(index, true);
+            return `${before.toFixed(1)} → ${after.toFixed(1)} ${unit}`;
+        };
+        exampleQualitySpan.textContent = `SNR ${metric(0, 'dB')}, THD ${metric(1, 'dB')}, ` +
+            `SINAD ${metric(2, 'dB')}, ENOB ${metric(3, 'bits')}`;
+    }
+
+    // Report the period of the signal found by autocorrelation
+    function showExamplePeriod() {
+        const period = WASM.example_period();
+        examplePeriodSpan.textContent = Number.isFinite(period)
+            ? `Period ${period.toPrecision(4)} s (${(1 / period).toPrecision(4)} Hz)`
+            : 'No period detected';
+    }
+
     // Track current example
-    let currentExample = 0; // 0=step, 1=sine, 2=square, 3=triangle
+    let currentExample = 0; // 0=step, 1=sine, 2=square, 3=triangle, 4=chirp, -1=user signal
     function plotCurrentExample() {
         const kMin = parseInt(exampleFreqMinInput.value, 10);
         const kMax = parseInt(exampleMaxFreqInput.value, 10);
-        WASM.plot_example(kMin, kMax, currentExample);
+        const ok = currentExample < 0 ? WASM.replot_example(kMin, kMax) : WASM.plot_example(kMin, kMax, currentExample);
+        showExampleResult(ok);
+        showExampleQuality();
+        showExamplePeriod();
+    }
+
+    // Copy samples into WASM memory and analyse them in place of the examples
+    function plotUserSignal(samples: Float32Array, sampleRate: number) {
+        const kMin = parseInt(exampleFreqMinInput.value, 10);
+        const kMax = parseInt(exampleMaxFreqInput.value, 10);
+        const ptr = WASM.alloc_f32_buffer(samples.length);
+        new Float32Array(WASM.memory.buffer, ptr, samples.length).set(samples);
+        const ok = WASM.plot_signal(ptr, samples.length, sampleRate, kMin, kMax);
+        WASM.free_buffer(ptr, samples.length);
+        showUserSignalResult(ok);
+    }
+
+    // Copy a CSV/TSV file into WASM memory and analyse the selected columns (1-based, 0 = none)
+    async function plotCsvFile(file: File, timeColumn: number, valueColumn: number, imaginaryColumn: number, sampleRate: number) {
+        const kMin = parseInt(exampleFreqMinInput.value, 10);
+        const kMax = parseInt(exampleMaxFreqInput.value, 10);
+        const bytes = new Uint8Array(await file.arrayBuffer());
+        const ptr = WASM.alloc_u8_buffer(bytes.length);
+        new Uint8Array(WASM.memory.buffer, ptr, bytes.length).set(bytes);
+        const ok = WASM.load_csv(ptr, bytes.length, timeColumn, valueColumn, imaginaryColumn, sampleRate, kMin, kMax);
+        WASM.free_u8_buffer(ptr, bytes.length);
+        showUserSignalResult(ok);
+    }
+
+    // Copy a WAV file into WASM memory and analyse one channel (1-based) or their average (0)
+    async function plotWavFile(file: File, channel: number) {
+        const kMin = parseInt(exampleFreqMinInput.value, 10);
+        const kMax = parseInt(exampleMaxFreqInput.value, 10);
+        const bytes = new Uint8Array(await file.arrayBuffer());
+        const ptr = WASM.alloc_u8_buffer(bytes.length);
+        new Uint8Array(WASM.memory.buffer, ptr, bytes.length).set(bytes);
+        const ok = WASM.load_wav(ptr, bytes.length, channel, kMin, kMax);
+        WASM.free_u8_buffer(ptr, bytes.length);
+        showUserSignalResult(ok);
+    }
+
+    // Copy an expression of t into WASM memory and analyse its samples over [tStart, tEnd)
+    function plotExpression(text: string, tStart: number, tEnd: number, numSamples: number) {
+        const kMin = parseInt(exampleFreqMinInput.value, 10);
+        const kMax = parseInt(exampleMaxFreqInput.value, 10);
+        const bytes = new TextEncoder().encode(text);
+        const ptr = WASM.alloc_u8_buffer(bytes.length);
+        new Uint8Array(WASM.memory.buffer, ptr, bytes.length).set(bytes);
+        const ok = WASM.plot_expression(ptr, bytes.length, tStart, tEnd, numSamples, kMin, kMax);
+        WASM.free_u8_buffer(ptr, bytes.length);
+        showUserSignalResult(ok);
+    }
+
+    // A loaded signal replaces the examples until one is selected again
+    function showUserSignalResult(ok: boolean) {
+        showExampleResult(ok);
+        if (ok) {
+            currentExample = -1;
+            document.querySelectorAll('.example-btn').forEach(b => b.classList.remove('active'));
+        }
+        showExampleQuality();
+        showExamplePeriod();
     }
     exampleMaxFreqInput.addEventListener('change', plotCurrentExample);
     exampleFreqMinInput.addEventListener('change', plotCurrentExample);
@@ -47,10 +176,197 @@ document.addEventListener('DOMContentLoaded', async () => {
         });
     });
 
+    // Signal typed or pasted by the user
+    const customSignalInput = document.getElementById('custom-signal')! as HTMLTextAreaElement;
+    const customSampleRateInput = document.getElementById('custom-sample-rate')! as HTMLInputElement;
+    document.getElementById('custom-signal-load')!.addEventListener('click', () => {
+        const values = customSignalInput.value.split(/[\s,;]+/).filter(v => v.length > 0).map(Number);
+        plotUserSignal(Float32Array.from(values), parseFloat(customSampleRateInput.value));
+    });
+
+    // Signal defined by an expression of t
+    const expressionInput = document.getElementById('expression')! as HTMLInputElement;
+    const expressionStartInput = document.getElementById('expression-start')! as HTMLInputElement;
+    const expressionEndInput = document.getElementById('expression-end')! as HTMLInputElement;
+    const expressionSamplesInput = document.getElementById('expression-samples')! as HTMLInputElement;
+    const loadExpression = () => plotExpression(expressionInput.value, parseFloat(expressionStartInput.value),
+        parseFloat(expressionEndInput.value), parseInt(expressionSamplesInput.value, 10));
+    document.getElementById('expression-plot')!.addEventListener('click', loadExpression);
+    expressionInput.addEventListener('keydown', (e) => {
+        if (e.key === 'Enter') loadExpression();
+    });
+
+    // Signal read from a CSV/TSV file; without a time column the sample rate above is used
+    const csvFileInput = document.getElementById('csv-file')! as HTMLInputElement;
+    const csvTimeColumnInput = document.getElementById('csv-time-column')! as HTMLInputElement;
+    const csvValueColumnInput = document.getElementById('csv-value-column')! as HTMLInputElement;
+    const csvImaginaryColumnInput = document.getElementById('csv-imaginary-column')! as HTMLInputElement;
+    const loadCsv = () => {
+        const file = csvFileInput.files?.[0];
+        if (!file) return;
+        plotCsvFile(file, parseInt(csvTimeColumnInput.value, 10), parseInt(csvValueColumnInput.value, 10),
+            parseInt(csvImaginaryColumnInput.value, 10), parseFloat(customSampleRateInput.value));
+    };
+    csvFileInput.addEventListener('change', loadCsv);
+    document.getElementById('csv-load')!.addEventListener('click', loadCsv);
+
+    // Audio clip from a WAV file, chosen with the file input or dropped onto the page
+    const wavFileInput = document.getElementById('wav-file')! as HTMLInputElement;
+    const wavChannelSelect = document.getElementById('wav-channel')! as HTMLSelectElement;
+    let wavFile: File | null = null;
+    const loadWav = () => {
+        if (wavFile) plotWavFile(wavFile, parseInt(wavChannelSelect.value, 10));
+    };
+    wavFileInput.addEventListener('change', () => {
+        wavFile = wavFileInput.files?.[0] ?? null;
+        loadWav();
+    });
+    wavChannelSelect.addEventListener('change', loadWav);
+    document.addEventListener('dragover', (e) => e.preventDefault());
+    document.addEventListener('drop', (e) => {
+        const file = e.dataTransfer?.files[0];
+        if (!file) return;
+        e.preventDefault();
+        if (file.name.toLowerCase().endsWith('.wav') || file.type.startsWith('audio/')) {
+            wavFile = file;
+            loadWav();
+        } else {
+            plotCsvFile(file, parseInt(csvTimeColumnInput.value, 10), parseInt(csvValueColumnInput.value, 10),
+                parseInt(csvImaginaryColumnInput.value, 10), parseFloat(customSampleRateInput.value));
+        }
+    });
+
+    // Download the filtered signal as a WAV file
+    const wavExportFormatSelect = document.getElementById('wav-export-format')! as HTMLSelectElement;
+    const wavExportNormalizeInput = document.getElementById('wav-export-normalize')! as HTMLInputElement;
+    document.getElementById('wav-export')!.addEventListener('click', () => {
+        const ok = WASM.export_wav(parseInt(wavExportFormatSelect.value, 10), wavExportNormalizeInput.checked);
+        showExampleResult(ok);
+        if (!ok) return;
+        // Copy the bytes out of WASM memory, which the next export reuses
+        const bytes = new Uint8Array(WASM.memory.buffer, WASM.export_wav_ptr(), WASM.export_wav_len()).slice();
+        const url = URL.createObjectURL(new Blob([bytes], { type: 'audio/wav' }));
+        const link = document.createElement('a');
+        link.href = url;
+        link.download = 'filtered.wav';
+        link.click();
+        URL.revokeObjectURL(url);
+    });
+
+    // Apply a setting of the example plot: replot with it, or show why it was rejected
+    function applyExampleSetting(ok: boolean) {
+        if (ok) {
+            plotCurrentExample();
+        } else {
+            showExampleResult(false);
+        }
+    }
+
+    // Envelope overlay
+    const exampleEnvelopeInput = document.getElementById('example-envelope')! as HTMLInputElement;
+    exampleEnvelopeInput.addEventListener('change', () => {
+        WASM.set_example_envelope(exampleEnvelopeInput.checked);
+        plotCurrentExample();
+    });
+
+    // Shape of the frequency-range mask (tapered edges reduce the Gibbs overshoot)
+    const exampleRangeShapeSelect = document.getElementById('example-range-shape')! as HTMLSelectElement;
+    const exampleRangeShapeParamInput = document.getElementById('example-range-shape-param')! as HTMLInputElement;
+    function updateExampleRangeShape() {
+        applyExampleSetting(WASM.set_example_range_shape(parseInt(exampleRangeShapeSelect.value, 10),
+                                                         parseFloat(exampleRangeShapeParamInput.value)));
+    }
+    exampleRangeShapeSelect.addEventListener('change', updateExampleRangeShape);
+    exampleRangeShapeParamInput.addEventListener('change', updateExampleRangeShape);
+
+    // Noise added to the examples
+    const exampleNoiseSelect = document.getElementById('example-noise')! as HTMLSelectElement;
+    const exampleNoiseSnrInput = document.getElementById('example-noise-snr')! as HTMLInputElement;
+    const exampleNoiseSeedInput = document.getElementById('example-noise-seed')! as HTMLInputElement;
+    function updateExampleNoise() {
+        applyExampleSetting(WASM.set_example_noise(
+            parseInt(exampleNoiseSelect.value, 10),
+            parseFloat(exampleNoiseSnrInput.value),
+            Math.max(0, parseInt(exampleNoiseSeedInput.value, 10) || 0),
+        ));
+    }
+    [exampleNoiseSelect, exampleNoiseSnrInput, exampleNoiseSeedInput].forEach(el => el.addEventListener('change', updateExampleNoise));
+
+    // Basis of the frequency range (DFT or DCT), to compare how well each compacts the energy
+    const exampleBasisSelect = document.getElementById('example-basis')! as HTMLSelectElement;
+    exampleBasisSelect.addEventListener('change', () => {
+        applyExampleSetting(WASM.set_example_basis(parseInt(exampleBasisSelect.value, 10)));
+    });
+
+    // Time-domain filter overlaid on the example instead of the frequency range
+    const exampleFilterSelect = document.getElementById('example-filter')! as HTMLSelectElement;
+    const exampleFilterResponseSelect = document.getElementById('example-filter-response')! as HTMLSelectElement;
+    const exampleFilterOrderInput = document.getElementById('example-filter-order')! as HTMLInputElement;
+    const exampleFilterCutoffInput = document.getElementById('example-filter-cutoff')! as HTMLInputElement;
+    const exampleFilterCutoffHighInput = document.getElementById('example-filter-cutoff-high')! as HTMLInputElement;
+    const exampleFilterZeroPhaseInput = document.getElementById('example-filter-zero-phase')! as HTMLInputElement;
+    function updateExampleFilter() {
+        applyExampleSetting(WASM.set_example_filter(
+            parseInt(exampleFilterSelect.value, 10),
+            parseInt(exampleFilterResponseSelect.value, 10),
+            parseInt(exampleFilterOrderInput.value, 10),
+            parseFloat(exampleFilterCutoffInput.value),
+            parseFloat(exampleFilterCutoffHighInput.value),
+            exampleFilterZeroPhaseInput.checked,
+        ));
+    }
+    [exampleFilterSelect, exampleFilterResponseSelect, exampleFilterOrderInput, exampleFilterCutoffInput,
+        exampleFilterCutoffHighInput, exampleFilterZeroPhaseInput].forEach(el => el.addEventListener('change', updateExampleFilter));
+
     // Initial plot and highlight
     document.querySelector('.example-btn[data-example="step"]')?.classList.add('active');
     plotCurrentExample();
 
+    // Spectrum window selection
+    const spectrumWindowSelect = document.getElementById('spectrum-window')! as HTMLSelectElement;
+    const spectrumWindowParamInput = document.getElementById('spectrum-window-param')! as HTMLInputElement;
+    function updateSpectrumWindow() {
+        const kind = parseInt(spectrumWindowSelect.value, 10);
+        const parameter = parseFloat(spectrumWindowParamInput.value);
+        showExampleResult(WASM.set_spectrum_window(kind, parameter));
+    }
+    spectrumWindowSelect.addEventListener('change', updateSpectrumWindow);
+    spectrumWindowParamInput.addEventListener('change', updateSpectrumWindow);
+
+    // Spectrum zero-padding selection
+    const spectrumPaddingSelect = document.getElementById('spectrum-padding')! as HTMLSelectElement;
+    spectrumPaddingSelect.addEventListener('change', () => {
+        showExampleResult(WASM.set_spectrum_padding(parseInt(spectrumPaddingSelect.value, 10)));
+    });
+
+    // Spectrum estimator selection
+    const spectrumEstimatorSelect = document.getElementById('spectrum-estimator')! as HTMLSelectElement;
+    spectrumEstimatorSelect.addEventListener('change', () => {
+        showExampleResult(WASM.set_spectrum_estimator(parseInt(spectrumEstimatorSelect.value, 10)));
+    });
+
+    // Phase panels below the example and animation spectra
+    const spectrumPhaseSelect = document.getElementById('spectrum-phase')! as HTMLSelectElement;
+    spectrumPhaseSelect.addEventListener('change', () => {
+        const view = parseInt(spectrumPhaseSelect.value, 10);
+        document.querySelectorAll<HTMLCanvasElement>('.phase-panel').forEach(panel => {
+            panel.style.display = view === 0 ? 'none' : 'block';
+        });
+        showExampleResult(WASM.set_spectrum_phase(view));
+    });
+
+    // Number of annotated spectrum peaks
+    const spectrumPeaksInput = document.getElementById('spectrum-peaks')! as HTMLInputElement;
+    spectrumPeaksInput.addEventListener('change', () => {
+        showExampleResult(WASM.set_spectrum_peaks(Math.max(0, parseInt(spectrumPeaksInput.value, 10) || 0)));
+    });
+
+    // Wavelet of the scalogram
+    const scalogramWaveletSelect = document.getElementById('scalogram-wavelet')! as HTMLSelectElement;
+    scalogramWaveletSelect.addEventListener('change', () => {
+        showExampleResult(WASM.set_scalogram_wavelet(parseInt(scalogramWaveletSelect.value, 10)));
+    });
+
     // Animation controls
     const animationFreqMinInput = document.getElementById('animation-freq-min')! as HTMLInputElement;
     const animationFreqMaxInput = document.getElementById('animation-freq-max')! as HTMLInputElement;
@@ -99,4 +415,53 @@ document.addEventListener('DOMContentLoaded', async () => {
     playPauseBtn.click();
     setTimeout(() => { playPauseBtn.click(); }, 5);
 
+    // Image filtering controls
+    const imageCanvas = document.getElementById('image-canvas')! as HTMLCanvasElement;
+    const imageFileInput = document.getElementById('image-file')! as HTMLInputElement;
+    const imageMaskSelect = document.getElementById('image-mask')! as HTMLSelectElement;
+    const imageRadiusInput = document.getElementById('image-radius')! as HTMLInputElement;
+    const imageOuterRadiusInput = document.getElementById('image-outer-radius')! as HTMLInputElement;
+    const imageErrorSpan = document.getElementById('image-error')! as HTMLSpanElement;
+
+    // Show the message of the last failed image call (or clear it after a successful one)
+    function showImageResult(ok: boolean) {
+        imageErrorSpan.textContent = ok ? '' : lastError();
+    }
+
+    function filterImage() {
+        showImageResult(WASM.filter_image(parseInt(imageMaskSelect.value, 10),
+                                          parseFloat(imageRadiusInput.value),
+                                          parseFloat(imageOuterRadiusInput.value)));
+    }
+    imageMaskSelect.addEventListener('change', filterImage);
+    imageRadiusInput.addEventListener('change', filterImage);
+    imageOuterRadiusInput.addEventListener('change', filterImage);
+
+    imageFileInput.addEventListener('change', () => {
+        const file = imageFileInput.files?.[0];
+        if (!file) { return; }
+        const image = new Image();
+        image.onload = () => {
+            // Downscale and draw the image, then read back its RGBA pixels
+            const scale = Math.min(1, MAX_IMAGE_SIZE / Math.max(image.width, image.height));
+            const width = Math.max(1, Math.round(image.width * scale));
+            const height = Math.max(1, Math.round(image.height * scale));
+            const ctx = imageCanvas.getContext('2d')!;
+            ctx.clearRect(0, 0, imageCanvas.width, imageCanvas.height);
+            ctx.drawImage(image, 0, 0, width, height);
+            const pixels = ctx.getImageData(0, 0, width, height).data;
+            ctx.drawImage(image, 0, 0, imageCanvas.width, imageCanvas.height);
+
+            // Copy the pixels into WASM memory
+            const ptr = WASM.alloc_u8_buffer(pixels.length);
+            new Uint8Array(WASM.memory.buffer, ptr, pixels.length).set(pixels);
+            const ok = WASM.load_image(ptr, width, height, 4);
+            WASM.free_u8_buffer(ptr, pixels.length);
+            showImageResult(ok);
+            if (ok) { filterImage(); }
+            URL.revokeObjectURL(image.src);
+        };
+        image.src = URL.createObjectURL(file);
+    });
+
 });